@@ -25,7 +25,7 @@ const html = fs.readFileSync(
     "utf8",
 );
 
-async function renderDom() {
+async function renderDom(tauri = null) {
     jest.useFakeTimers();
 
     const dom = new JSDOMClass(html, {
@@ -33,8 +33,8 @@ async function renderDom() {
         resources: "usable",
         url: "http://localhost",
         beforeParse(window) {
-            // Prevent Tauri calls during tests
-            window.__TAURI__ = null;
+            // Prevent Tauri calls during tests, unless a test mocks them
+            window.__TAURI__ = tauri;
             // Use Jest-controlled timers so we can advance time deterministically
             window.setTimeout = setTimeout;
             window.clearTimeout = clearTimeout;
@@ -46,6 +46,27 @@ async function renderDom() {
     return dom;
 }
 
+// The window presets get_presets would return, as the page only knows "m"
+function preset(name, size, padding) {
+    return {
+        name,
+        width: size,
+        height: size,
+        padding,
+        fontSize: 20,
+        controlFontSize: 12,
+        fontWeight: 400,
+        letterSpacing: 0,
+    };
+}
+
+const presets = [
+    preset("xs", 300, 20),
+    preset("s", 400, 26),
+    preset("m", 500, 30),
+    preset("l", 700, 40),
+];
+
 describe("Visual Countdown Timer behavior", () => {
     let dom;
 
@@ -84,8 +105,10 @@ describe("Visual Countdown Timer behavior", () => {
 
     test("applying presets keeps other body flags intact", async () => {
         dom = await renderDom();
-        const { document, setLabelVisibility, applyPreset } = dom.window;
+        const { document, setLabelVisibility, setWindowPresets, applyPreset } =
+            dom.window;
 
+        setWindowPresets(presets);
         setLabelVisibility(false);
         applyPreset("preset-l");
 
@@ -105,7 +128,7 @@ describe("Visual Countdown Timer behavior", () => {
         dom = await renderDom();
         const { document, startTimerFromMinute } = dom.window;
         const targetLine = document.querySelector(
-            '#tickMarks line[data-minute="1"]',
+            '#tickMarks line[data-tick="1"]',
         );
         const baseWidth = targetLine.getAttribute("stroke-width");
 
@@ -117,4 +140,458 @@ describe("Visual Countdown Timer behavior", () => {
         expect(targetLine.getAttribute("stroke")).toBe("#1f2937");
         expect(targetLine.getAttribute("stroke-width")).toBe(baseWidth);
     });
+
+    test("escape stops a running countdown", async () => {
+        dom = await renderDom();
+        const { document, startTimerFromMinute } = dom.window;
+        const redPath = document.getElementById("redPath");
+
+        startTimerFromMinute(10);
+        jest.advanceTimersByTime(1000);
+        expect(redPath.getAttribute("d")).not.toBe("");
+
+        document.dispatchEvent(
+            new dom.window.KeyboardEvent("keydown", { key: "Escape" }),
+        );
+        expect(redPath.getAttribute("d")).toBe("");
+    });
+
+    test("ctrl+p pauses and resumes the countdown", async () => {
+        dom = await renderDom();
+        const { document, startTimerFromMinute } = dom.window;
+        const redPath = document.getElementById("redPath");
+        const pressCtrlP = () =>
+            document.dispatchEvent(
+                new dom.window.KeyboardEvent("keydown", {
+                    key: "p",
+                    ctrlKey: true,
+                }),
+            );
+
+        startTimerFromMinute(5);
+        jest.advanceTimersByTime(3000);
+        pressCtrlP();
+        const pausedPath = redPath.getAttribute("d");
+        expect(document.body.classList.contains("timer-paused")).toBe(true);
+
+        jest.advanceTimersByTime(10000);
+        expect(redPath.getAttribute("d")).toBe(pausedPath);
+
+        pressCtrlP();
+        expect(document.body.classList.contains("timer-paused")).toBe(false);
+        jest.advanceTimersByTime(1000);
+        expect(redPath.getAttribute("d")).not.toBe(pausedPath);
+    });
+
+    test("shows when a restored timer finished while the app was closed", async () => {
+        dom = await renderDom();
+        const { document, renderTimerState } = dom.window;
+        const caption = document.getElementById("timerCaption");
+
+        expect(caption.hidden).toBe(true);
+
+        renderTimerState({
+            status: "finished",
+            remainingSeconds: 0,
+            label: "Tea",
+            finishedWhileAway: true,
+        });
+        expect(caption.hidden).toBe(false);
+        expect(caption.textContent).toBe("Finished while you were away: Tea");
+
+        renderTimerState({ status: "idle", remainingSeconds: 0 });
+        expect(caption.hidden).toBe(true);
+    });
+
+    test("typing a digit opens the duration field and Enter starts it", async () => {
+        dom = await renderDom();
+        const { document, KeyboardEvent } = dom.window;
+        const input = document.getElementById("durationInput");
+        const redPath = document.getElementById("redPath");
+
+        expect(input.hidden).toBe(true);
+        document.dispatchEvent(new KeyboardEvent("keydown", { key: "3" }));
+        expect(input.hidden).toBe(false);
+        expect(input.value).toBe("3");
+
+        input.value = "3x";
+        input.dispatchEvent(
+            new KeyboardEvent("keydown", { key: "Enter", bubbles: true }),
+        );
+        expect(input.classList.contains("invalid")).toBe(true);
+        expect(redPath.getAttribute("d")).toBe("");
+
+        input.value = "3";
+        input.dispatchEvent(
+            new KeyboardEvent("keydown", { key: "Enter", bubbles: true }),
+        );
+        expect(input.hidden).toBe(true);
+        expect(redPath.getAttribute("d")).not.toBe("");
+    });
+
+    test("a 5-minute dial labels each minute and ticks every second", async () => {
+        dom = await renderDom();
+        const { document, applyDialLayout } = dom.window;
+
+        applyDialLayout({
+            spanMinutes: 5,
+            spanSeconds: 300,
+            tickCount: 300,
+            tickSeconds: 1,
+            majorEvery: 5,
+            labelEvery: 60,
+            labelUnitSeconds: 60,
+        });
+
+        const labels = [...document.querySelectorAll("#dialLabels text")];
+        expect(labels.map((text) => text.textContent)).toEqual([
+            "0",
+            "1",
+            "2",
+            "3",
+            "4",
+        ]);
+        expect(document.querySelectorAll("#tickMarks line")).toHaveLength(300);
+
+        // Tick 150 is half the dial: the sector should cover exactly 180°
+        document
+            .querySelector('#tickMarks path[data-tick="150"]')
+            .dispatchEvent(new dom.window.MouseEvent("click"));
+        const d = document.getElementById("redPath").getAttribute("d");
+        expect(d).toMatch(/A 160 160 0 0 1 250 410/);
+    });
+
+    test("pomodoro phase colors the sector and shows the cycle in the knob", async () => {
+        dom = await renderDom();
+        const { document, renderTimerState } = dom.window;
+        const knobText = document.getElementById("knobText");
+
+        renderTimerState({
+            status: "running",
+            mode: "pomodoro",
+            remainingSeconds: 300,
+            pomodoro: { phase: "shortBreak", cycle: 3 },
+        });
+        expect(document.body.classList.contains("phase-short-break")).toBe(true);
+        expect(knobText.textContent).toBe("3");
+
+        renderTimerState({ status: "idle", remainingSeconds: 0 });
+        expect(document.body.classList.contains("phase-short-break")).toBe(false);
+        expect(knobText.textContent).toBe("");
+    });
+
+    test("a running sequence shows its step name and position", async () => {
+        dom = await renderDom();
+        const { document, renderTimerState } = dom.window;
+        const caption = document.getElementById("timerCaption");
+
+        renderTimerState({
+            status: "running",
+            mode: "sequence",
+            remainingSeconds: 20,
+            label: "HIIT",
+            sequence: { name: "HIIT", stepName: "Rest", step: 2, stepCount: 17 },
+        });
+        expect(caption.hidden).toBe(false);
+        expect(caption.textContent).toBe("HIIT · 2/17 Rest");
+    });
+
+    test("a stopwatch shows its elapsed time and laps, newest first", async () => {
+        dom = await renderDom();
+        const { document, renderTimerState } = dom.window;
+        const lapPanel = document.getElementById("lapPanel");
+
+        renderTimerState({
+            status: "running",
+            mode: "stopwatch",
+            remainingSeconds: 0,
+            elapsedMs: 95400,
+            stopwatch: {
+                laps: [
+                    { number: 1, splitMs: 61200, totalMs: 61200 },
+                    { number: 2, splitMs: 30000, totalMs: 91200 },
+                ],
+            },
+        });
+        expect(document.getElementById("timerCaption").textContent).toBe("1:35");
+        expect(lapPanel.hidden).toBe(false);
+        const laps = [...document.querySelectorAll("#lapList li")];
+        expect(laps.map((lap) => lap.textContent)).toEqual([
+            "2. 1:31.20:30.0",
+            "1. 1:01.21:01.2",
+        ]);
+
+        renderTimerState({ status: "idle", remainingSeconds: 0 });
+        expect(lapPanel.hidden).toBe(true);
+    });
+
+    test("a countdown to a wall-clock time shows its target", async () => {
+        dom = await renderDom();
+        const { document, renderTimerState } = dom.window;
+        const caption = document.getElementById("timerCaption");
+
+        // Beyond the 60-minute dial: full sector plus the time still to go
+        renderTimerState({
+            status: "running",
+            mode: "countdown",
+            remainingSeconds: 5400,
+            target: "14:30",
+            label: "Standup",
+        });
+        expect(caption.textContent).toBe("Until 14:30 · Standup (1:30:00 left)");
+
+        renderTimerState({
+            status: "running",
+            mode: "countdown",
+            remainingSeconds: 1200,
+            target: "14:30",
+        });
+        expect(caption.textContent).toBe("Until 14:30");
+    });
+
+    test("overtime shows the overrun sector and keeps it once stopped", async () => {
+        dom = await renderDom();
+        const { document, renderTimerState } = dom.window;
+        const caption = document.getElementById("timerCaption");
+        const redPath = document.getElementById("redPath");
+
+        renderTimerState({
+            status: "overtime",
+            mode: "countdown",
+            remainingSeconds: 0,
+            overrunMs: 900000,
+        });
+        expect(document.body.classList.contains("timer-overtime")).toBe(true);
+        expect(caption.textContent).toBe("+15:00");
+        // A quarter of the 60-minute dial
+        expect(redPath.getAttribute("d")).toMatch(/A 160 160 0 0 1 410 250/);
+
+        renderTimerState({
+            status: "finished",
+            mode: "countdown",
+            remainingSeconds: 0,
+            overrunMs: 912000,
+        });
+        expect(document.body.classList.contains("timer-overtime")).toBe(true);
+        expect(caption.textContent).toBe("Over by 15:12");
+
+        renderTimerState({ status: "idle", remainingSeconds: 0, overrunMs: 0 });
+        expect(document.body.classList.contains("timer-overtime")).toBe(false);
+    });
+
+    test("a countdown that ran out offers +5 min and Restart", async () => {
+        const invoke = jest.fn(() => new Promise(() => {}));
+        dom = await renderDom({
+            core: { invoke },
+            event: { listen: () => Promise.resolve(() => {}) },
+            webviewWindow: {
+                getCurrentWebviewWindow: () => ({
+                    listen: () => Promise.resolve(() => {}),
+                }),
+            },
+        });
+        const { document, renderTimerState } = dom.window;
+        const panel = document.getElementById("finishedPanel");
+
+        renderTimerState({
+            status: "running",
+            mode: "countdown",
+            remainingSeconds: 60,
+        });
+        expect(panel.hidden).toBe(true);
+
+        renderTimerState({
+            status: "finished",
+            mode: "countdown",
+            remainingSeconds: 0,
+        });
+        expect(panel.hidden).toBe(false);
+        document.getElementById("extendTimer").click();
+        document.getElementById("restartTimer").click();
+        const commands = invoke.mock.calls.map(([command]) => command);
+        expect(commands).toContain("extend_timer");
+        expect(commands).toContain("restart_timer");
+
+        renderTimerState({
+            status: "finished",
+            mode: "stopwatch",
+            remainingSeconds: 0,
+        });
+        expect(panel.hidden).toBe(true);
+    });
+
+    test("a pre-end warning flashes the dial for a moment", async () => {
+        dom = await renderDom();
+        const { document, flashWarning } = dom.window;
+
+        flashWarning();
+        expect(document.body.classList.contains("warning-flash")).toBe(true);
+
+        jest.advanceTimersByTime(1500);
+        expect(document.body.classList.contains("warning-flash")).toBe(false);
+    });
+
+    test("overlay mode makes the page see-through at the chosen opacity", async () => {
+        dom = await renderDom();
+        const { document, applyOverlay } = dom.window;
+
+        applyOverlay({ active: true, opacity: 0.4 });
+        expect(document.body.classList.contains("overlay")).toBe(true);
+        expect(
+            document.body.style.getPropertyValue("--overlay-opacity"),
+        ).toBe("0.4");
+
+        applyOverlay({ active: false, opacity: 0.4 });
+        expect(document.body.classList.contains("overlay")).toBe(false);
+        expect(document.body.classList.contains("preset-m")).toBe(true);
+    });
+
+    test("a restored window view applies its preset and label visibility", async () => {
+        dom = await renderDom();
+        const { document, setWindowPresets, applyWindowView } = dom.window;
+
+        setWindowPresets(presets);
+        applyWindowView({ preset: "preset-s", labelsVisible: false });
+        expect(document.body.classList.contains("preset-s")).toBe(true);
+        expect(document.body.classList.contains("preset-m")).toBe(false);
+        expect(document.body.classList.contains("labels-hidden")).toBe(true);
+    });
+
+    test("inside Tauri the presets come from the settings file", async () => {
+        const replies = {
+            get_presets: presets,
+            get_window_view: { preset: "preset-l", labelsVisible: true },
+        };
+        // Anything else stays pending, so only the presets get applied
+        const invoke = jest.fn((command) =>
+            command in replies
+                ? Promise.resolve(replies[command])
+                : new Promise(() => {}),
+        );
+        dom = await renderDom({
+            core: { invoke },
+            event: { listen: () => Promise.resolve(() => {}) },
+            webviewWindow: {
+                getCurrentWebviewWindow: () => ({
+                    listen: () => Promise.resolve(() => {}),
+                }),
+            },
+        });
+        const { document } = dom.window;
+
+        await jest.advanceTimersByTimeAsync(0);
+        expect(document.body.classList.contains("preset-l")).toBe(true);
+        expect(document.body.style.getPropertyValue("--padding")).toBe("40px");
+    });
+
+    test("user-defined presets set their padding and fonts, Ctrl+digit picks them", async () => {
+        dom = await renderDom();
+        const { document, setWindowPresets, KeyboardEvent } = dom.window;
+
+        setWindowPresets([
+            {
+                name: "tiny",
+                width: 200,
+                height: 200,
+                padding: 4,
+                fontSize: 16,
+                controlFontSize: 8,
+                fontWeight: 700,
+                letterSpacing: 0,
+            },
+            {
+                name: "wall",
+                width: 680,
+                height: 680,
+                padding: 48,
+                fontSize: 32,
+                controlFontSize: 16,
+                fontWeight: 300,
+                letterSpacing: 1,
+            },
+        ]);
+        document.dispatchEvent(
+            new KeyboardEvent("keydown", { key: "2", ctrlKey: true }),
+        );
+
+        expect(document.body.classList.contains("preset-wall")).toBe(true);
+        expect(document.body.classList.contains("preset-m")).toBe(false);
+        expect(document.body.style.getPropertyValue("--padding")).toBe("48px");
+        expect(document.body.style.getPropertyValue("--font-size")).toBe("32px");
+    });
+
+    test("ctrl+alt+digit snaps the window instead of switching presets", async () => {
+        dom = await renderDom();
+        const { document, setWindowPresets, KeyboardEvent } = dom.window;
+        setWindowPresets(presets);
+        const event = new KeyboardEvent("keydown", {
+            key: "2",
+            code: "Digit2",
+            ctrlKey: true,
+            altKey: true,
+            cancelable: true,
+        });
+
+        document.dispatchEvent(event);
+        expect(event.defaultPrevented).toBe(true);
+        expect(document.body.classList.contains("preset-m")).toBe(true);
+        expect(document.body.classList.contains("preset-s")).toBe(false);
+    });
+
+    test("puck mode shows the draggable bezel around the dial", async () => {
+        dom = await renderDom();
+        const { document, applyPuck } = dom.window;
+        const bezel = document.getElementById("puckBezel");
+
+        expect(bezel.hasAttribute("data-tauri-drag-region")).toBe(true);
+
+        applyPuck(true);
+        expect(document.body.classList.contains("puck")).toBe(true);
+
+        applyPuck(false);
+        expect(document.body.classList.contains("puck")).toBe(false);
+    });
+
+    test("the presentation cursor hides when idle and comes back on movement", async () => {
+        dom = await renderDom();
+        const { document, hideCursorWhenIdle, MouseEvent } = dom.window;
+
+        hideCursorWhenIdle();
+        expect(document.body.classList.contains("cursor-hidden")).toBe(false);
+
+        jest.advanceTimersByTime(2500);
+        expect(document.body.classList.contains("cursor-hidden")).toBe(true);
+
+        document.dispatchEvent(new MouseEvent("mousemove"));
+        expect(document.body.classList.contains("cursor-hidden")).toBe(false);
+    });
+
+    test("Ctrl+N adds a timer and ticks are only taken for this window", async () => {
+        // Pending forever, so nothing from the engine gets rendered
+        const invoke = jest.fn(() => new Promise(() => {}));
+        const listen = jest.fn(() => Promise.resolve(() => {}));
+        const windowListen = jest.fn(() => Promise.resolve(() => {}));
+        dom = await renderDom({
+            core: { invoke },
+            event: { listen },
+            webviewWindow: {
+                getCurrentWebviewWindow: () => ({ listen: windowListen }),
+            },
+        });
+        const { document, KeyboardEvent } = dom.window;
+
+        expect(windowListen).toHaveBeenCalledWith(
+            "timer://tick",
+            expect.any(Function),
+        );
+        expect(listen).not.toHaveBeenCalledWith(
+            "timer://tick",
+            expect.any(Function),
+        );
+
+        document.dispatchEvent(
+            new KeyboardEvent("keydown", { key: "n", ctrlKey: true }),
+        );
+        expect(invoke).toHaveBeenCalledWith("create_timer");
+    });
 });
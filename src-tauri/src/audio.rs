@@ -0,0 +1,279 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use rodio::source::{SineWave, Source, Zero};
+use rodio::{Decoder, OutputStream, OutputStreamBuilder, Sink};
+use serde::{Deserialize, Serialize};
+
+const MAX_REPEAT: u32 = 10;
+const SOUND_EXTENSIONS: [&str; 3] = ["wav", "ogg", "flac"];
+/// The alarm key of previews, which belong to no timer.
+pub const PREVIEW: &str = "preview";
+
+/// The alarm played when a timer finishes: one of the chimes built into
+/// the app, or an audio file chosen by the user.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum AlarmSound {
+    Chime,
+    Bell,
+    Beep,
+    File(PathBuf),
+}
+
+/// Sound options, part of the settings file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SoundConfig {
+    pub enabled: bool,
+    pub alarm: AlarmSound,
+    /// 0.0 (silent) to 1.0 (full volume).
+    pub volume: f32,
+    /// How many times the alarm plays in a row.
+    pub repeat: u32,
+    /// A soft chime as the dial flashes a tick on a whole minute left:
+    /// every minute, or every tick on dials longer than an hour.
+    pub minute_chime: bool,
+}
+
+impl Default for SoundConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            alarm: AlarmSound::Chime,
+            volume: 0.8,
+            repeat: 2,
+            minute_chime: false,
+        }
+    }
+}
+
+impl SoundConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0.0..=1.0).contains(&self.volume) {
+            return Err("Volume must be between 0 and 1".to_string());
+        }
+        if !(1..=MAX_REPEAT).contains(&self.repeat) {
+            return Err(format!("Repeat count must be between 1 and {}", MAX_REPEAT));
+        }
+        Ok(())
+    }
+}
+
+/// Whether `path` looks like a file that can be used as the alarm.
+pub fn is_sound_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| SOUND_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+}
+
+/// Reads and decodes an alarm file once, so a broken one is refused when
+/// it is chosen rather than when the timer runs out.
+pub fn check_file(path: &Path) -> Result<(), String> {
+    load_file(path).map(|_| ())
+}
+
+// Alarms are keyed by the timer that rang them, so each timer only ever
+// silences its own.
+enum Request {
+    Alarm(String, SoundConfig),
+    MinuteChime(f32),
+    Warning(f32),
+    Stop(String),
+}
+
+/// Managed as Tauri state. Playback runs on its own thread, which owns the
+/// audio device, so sounds play whether or not the window is visible.
+pub struct AudioPlayer {
+    requests: Mutex<Sender<Request>>,
+}
+
+impl Default for AudioPlayer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl AudioPlayer {
+    pub fn new() -> Self {
+        let (requests, receiver) = mpsc::channel();
+        thread::spawn(move || play_requests(receiver));
+        Self {
+            requests: Mutex::new(requests),
+        }
+    }
+
+    /// Plays the alarm of timer `key`, cutting off that timer's alarm if it
+    /// is still playing.
+    pub fn play_alarm(&self, key: &str, config: &SoundConfig) {
+        if config.enabled {
+            self.send(Request::Alarm(key.to_string(), config.clone()));
+        }
+    }
+
+    pub fn play_minute_chime(&self, config: &SoundConfig) {
+        if config.enabled && config.minute_chime {
+            self.send(Request::MinuteChime(config.volume));
+        }
+    }
+
+    /// A short rising cue for a pre-end warning, played even when the
+    /// minute chime is off.
+    pub fn play_warning(&self, config: &SoundConfig) {
+        if config.enabled {
+            self.send(Request::Warning(config.volume));
+        }
+    }
+
+    /// Silences the alarm of timer `key`, e.g. when that timer is stopped.
+    pub fn stop(&self, key: &str) {
+        self.send(Request::Stop(key.to_string()));
+    }
+
+    fn send(&self, request: Request) {
+        if self.requests.lock().unwrap().send(request).is_err() {
+            eprintln!("Failed to play sound: audio thread has stopped");
+        }
+    }
+}
+
+// The output device is only opened once there is something to play, and
+// kept open after that.
+fn play_requests(receiver: Receiver<Request>) {
+    let mut stream: Option<OutputStream> = None;
+    let mut alarms: HashMap<String, Sink> = HashMap::new();
+
+    for request in receiver {
+        if let Request::Stop(key) = &request {
+            if let Some(sink) = alarms.remove(key) {
+                sink.stop();
+            }
+            continue;
+        }
+
+        if stream.is_none() {
+            match OutputStreamBuilder::open_default_stream() {
+                Ok(mut opened) => {
+                    opened.log_on_drop(false);
+                    stream = Some(opened);
+                }
+                Err(e) => {
+                    eprintln!("Failed to open audio output: {}", e);
+                    continue;
+                }
+            }
+        }
+        let Some(mixer) = stream.as_ref().map(OutputStream::mixer) else {
+            continue;
+        };
+
+        match request {
+            Request::Alarm(key, config) => {
+                let sink = Sink::connect_new(mixer);
+                sink.set_volume(config.volume);
+                let sound = alarm_source(&config.alarm).buffered();
+                for _ in 0..config.repeat {
+                    sink.append(sound.clone());
+                }
+                // Replacing the previous sink stops whatever it was playing.
+                alarms.insert(key, sink);
+            }
+            Request::MinuteChime(volume) => {
+                let sink = Sink::connect_new(mixer);
+                sink.set_volume(volume * 0.5);
+                sink.append(tone(1320.0, 0.25, 0.3));
+                sink.detach();
+            }
+            Request::Warning(volume) => {
+                let sink = Sink::connect_new(mixer);
+                sink.set_volume(volume);
+                sink.append(sequence(vec![
+                    tone(784.0, 0.2, 0.4),
+                    tone(1175.0, 0.4, 0.4),
+                ]));
+                sink.detach();
+            }
+            Request::Stop(_) => {}
+        }
+    }
+}
+
+type BoxedSource = Box<dyn Source + Send>;
+
+// A file that went missing or can't be decoded falls back to the chime,
+// so the timer never ends silently.
+fn alarm_source(alarm: &AlarmSound) -> BoxedSource {
+    match alarm {
+        AlarmSound::Chime => chime(),
+        AlarmSound::Bell => bell(),
+        AlarmSound::Beep => beep(),
+        AlarmSound::File(path) => load_file(path).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            chime()
+        }),
+    }
+}
+
+fn load_file(path: &Path) -> Result<BoxedSource, String> {
+    let data = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let decoder = Decoder::new(Cursor::new(data))
+        .map_err(|e| format!("Failed to decode {}: {}", path.display(), e))?;
+    Ok(Box::new(decoder))
+}
+
+// The built-in sounds are synthesized rather than shipped as files: sine
+// tones that fade out, strung together with short pauses.
+fn tone(frequency: f32, seconds: f32, amplitude: f32) -> BoxedSource {
+    let length = Duration::from_secs_f32(seconds);
+    Box::new(
+        SineWave::new(frequency)
+            .take_duration(length)
+            .amplify(amplitude)
+            .fade_out(length),
+    )
+}
+
+fn pause(seconds: f32) -> BoxedSource {
+    Box::new(Zero::new(1, 48_000).take_duration(Duration::from_secs_f32(seconds)))
+}
+
+// Plays the parts one after another as a single source.
+fn sequence(parts: Vec<BoxedSource>) -> BoxedSource {
+    let (queue, output) = rodio::queue::queue(false);
+    for part in parts {
+        queue.append(part);
+    }
+    Box::new(output)
+}
+
+// Two falling notes, E6 then C6.
+fn chime() -> BoxedSource {
+    sequence(vec![
+        tone(1318.5, 0.6, 0.5),
+        tone(1046.5, 0.9, 0.5),
+        pause(0.4),
+    ])
+}
+
+// A5 with a faint overtone, ringing out longer.
+fn bell() -> BoxedSource {
+    let ring = tone(880.0, 1.6, 0.5).mix(tone(2420.0, 0.8, 0.15));
+    sequence(vec![Box::new(ring), pause(0.4)])
+}
+
+fn beep() -> BoxedSource {
+    sequence(vec![
+        tone(1000.0, 0.15, 0.5),
+        pause(0.1),
+        tone(1000.0, 0.15, 0.5),
+        pause(0.1),
+        tone(1000.0, 0.15, 0.5),
+        pause(0.5),
+    ])
+}
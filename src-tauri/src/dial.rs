@@ -0,0 +1,136 @@
+use serde::Serialize;
+
+/// Dial spans offered in the app, in minutes.
+pub const DIAL_SPANS: [u32; 6] = [5, 15, 30, 60, 120, 720];
+
+pub const DEFAULT_DIAL_SPAN: u32 = 60;
+
+/// Everything the webview needs to draw the dial for a span: how many ticks
+/// there are, what each tick is worth, which ticks are long and labelled,
+/// and which unit the labels count in.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DialLayout {
+    pub span_minutes: u32,
+    pub span_seconds: u64,
+    pub tick_count: u32,
+    pub tick_seconds: u64,
+    pub major_every: u32,
+    pub label_every: u32,
+    pub label_unit_seconds: u64,
+}
+
+impl DialLayout {
+    /// How many seconds apart the minute chime sounds: on the flashed ticks
+    /// that fall on a whole minute left. Ticks either divide a minute or
+    /// are whole minutes, so that is every minute up to the one-hour dial
+    /// and every tick beyond it.
+    pub fn chime_seconds(&self) -> u64 {
+        self.tick_seconds.max(60)
+    }
+}
+
+pub fn check_span(minutes: u32) -> Result<(), String> {
+    if DIAL_SPANS.contains(&minutes) {
+        Ok(())
+    } else {
+        Err(format!(
+            "Unsupported dial span {} min, expected one of {:?}",
+            minutes, DIAL_SPANS
+        ))
+    }
+}
+
+/// The span after `minutes`, wrapping around to the shortest one.
+pub fn next_span(minutes: u32) -> u32 {
+    let index = DIAL_SPANS.iter().position(|&span| span == minutes);
+    match index {
+        Some(index) => DIAL_SPANS[(index + 1) % DIAL_SPANS.len()],
+        None => DEFAULT_DIAL_SPAN,
+    }
+}
+
+pub fn layout(span_minutes: u32) -> DialLayout {
+    // (tick seconds, long tick every, label every, label unit seconds).
+    // Spans from a quarter hour up keep 60 ticks so the dial reads like a
+    // clock face; the 5-minute one has a tick per second for short drills.
+    let (tick_seconds, major_every, label_every, label_unit_seconds) = match span_minutes {
+        // 1 s ticks, long ticks every 5 seconds, a label per minute
+        5 => (1, 5, 60, 60),
+        // 15 s ticks, a long tick and label per minute
+        15 => (15, 4, 4, 60),
+        // 30 s ticks, long ticks and labels every 5 minutes
+        30 => (30, 10, 10, 60),
+        // 2 min ticks, long ticks and labels every 10 minutes
+        120 => (120, 5, 5, 60),
+        // 12 min ticks, long ticks and labels every hour
+        720 => (720, 5, 5, 3600),
+        // The classic one-hour dial
+        _ => (60, 5, 5, 60),
+    };
+
+    let span_seconds = u64::from(span_minutes) * 60;
+    DialLayout {
+        span_minutes,
+        span_seconds,
+        tick_count: (span_seconds / tick_seconds) as u32,
+        tick_seconds,
+        major_every,
+        label_every,
+        label_unit_seconds,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_ticks_cover_every_span() {
+        for span in DIAL_SPANS {
+            let layout = layout(span);
+            assert_eq!(layout.span_seconds, u64::from(span) * 60);
+            assert_eq!(
+                layout.tick_seconds * u64::from(layout.tick_count),
+                layout.span_seconds
+            );
+            assert_eq!(layout.tick_count % layout.label_every, 0, "{} min", span);
+            assert_eq!(layout.tick_count % layout.major_every, 0, "{} min", span);
+        }
+    }
+
+    #[test]
+    fn the_five_minute_dial_ticks_every_second() {
+        let drill = layout(5);
+        assert_eq!(drill.tick_seconds, 1);
+        assert_eq!(drill.tick_count, 300);
+        // Labelled 0, 1, 2, 3, 4 minutes
+        assert_eq!(drill.tick_seconds * u64::from(drill.label_every), 60);
+        assert_eq!(layout(60).tick_count, 60);
+        assert_eq!(layout(720).tick_count, 60);
+    }
+
+    #[test]
+    fn labels_count_minutes_or_hours() {
+        assert_eq!(layout(60).label_unit_seconds, 60);
+        assert_eq!(layout(720).label_unit_seconds, 3600);
+        // 5 min ticks on the one-hour dial, labelled 0, 5, 10, ...
+        let hour = layout(60);
+        assert_eq!(hour.tick_seconds * u64::from(hour.label_every), 5 * 60);
+    }
+
+    #[test]
+    fn cycles_through_the_spans_in_order() {
+        assert_eq!(next_span(5), 15);
+        assert_eq!(next_span(60), 120);
+        assert_eq!(next_span(720), 5);
+        assert_eq!(next_span(42), DEFAULT_DIAL_SPAN);
+    }
+
+    #[test]
+    fn only_offered_spans_are_accepted() {
+        assert!(check_span(30).is_ok());
+        assert!(check_span(0).is_err());
+        assert!(check_span(45).is_err());
+    }
+}
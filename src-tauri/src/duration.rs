@@ -0,0 +1,168 @@
+/// The longest duration a timer can run: a week.
+pub const MAX_SECONDS: u64 = 7 * 24 * 60 * 60;
+
+/// Refuses durations no timer should run: zero, or longer than
+/// `MAX_SECONDS`.
+pub fn check_seconds(seconds: u64) -> Result<(), String> {
+    if seconds == 0 {
+        return Err("Duration must be longer than zero".to_string());
+    }
+    if seconds > MAX_SECONDS {
+        return Err(format!(
+            "Duration must be at most {} days",
+            MAX_SECONDS / (24 * 60 * 60)
+        ));
+    }
+    Ok(())
+}
+
+/// Parses a typed duration into whole seconds.
+///
+/// Accepted forms:
+/// - unit strings: `90s`, `7m30s`, `1h15m`, `1h 15m` (a trailing bare number
+///   takes the next smaller unit, so `7m30` is `7m30s`)
+/// - clock strings: `12:30` (minutes:seconds), `1:15:00` (hours:minutes:seconds)
+/// - a bare number, read as minutes like the dial ticks: `25`
+///
+/// Anything longer than `MAX_SECONDS` is refused.
+pub fn parse_duration(input: &str) -> Result<u64, String> {
+    let text = input.trim().to_ascii_lowercase();
+    if text.is_empty() {
+        return Err("Enter a duration such as 7m30s or 12:30".to_string());
+    }
+
+    let seconds = if text.contains(':') {
+        parse_clock(&text)
+    } else if text.bytes().all(|b| b.is_ascii_digit()) {
+        parse_number(&text).and_then(|minutes| scale(minutes, 60))
+    } else {
+        parse_units(&text)
+    }
+    .ok_or_else(|| format!("Could not read \"{}\" as a duration", input.trim()))?;
+
+    check_seconds(seconds)?;
+    Ok(seconds)
+}
+
+fn parse_clock(text: &str) -> Option<u64> {
+    let parts = text
+        .split(':')
+        .map(|part| parse_number(part.trim()))
+        .collect::<Option<Vec<_>>>()?;
+
+    match parts[..] {
+        [minutes, seconds] if seconds < 60 => scale(minutes, 60)?.checked_add(seconds),
+        [hours, minutes, seconds] if minutes < 60 && seconds < 60 => {
+            scale(hours, 3600)?.checked_add(minutes * 60 + seconds)
+        }
+        _ => None,
+    }
+}
+
+fn parse_units(text: &str) -> Option<u64> {
+    const UNITS: [(char, u64); 3] = [('h', 3600), ('m', 60), ('s', 1)];
+
+    let mut total: u64 = 0;
+    let mut digits = String::new();
+    // Index into UNITS of the next unit allowed; units must come in
+    // descending order and at most once each.
+    let mut next_unit = 0;
+
+    for c in text.chars() {
+        if c.is_ascii_digit() {
+            digits.push(c);
+        } else if c.is_whitespace() {
+            continue;
+        } else {
+            let index = UNITS.iter().position(|(unit, _)| *unit == c)?;
+            if index < next_unit || digits.is_empty() {
+                return None;
+            }
+            total = total.checked_add(scale(parse_number(&digits)?, UNITS[index].1)?)?;
+            digits.clear();
+            next_unit = index + 1;
+        }
+    }
+
+    if !digits.is_empty() {
+        // `7m30` means `7m30s`; a bare trailing number needs a unit before it.
+        if next_unit == 0 || next_unit >= UNITS.len() {
+            return None;
+        }
+        total = total.checked_add(scale(parse_number(&digits)?, UNITS[next_unit].1)?)?;
+    }
+    Some(total)
+}
+
+fn parse_number(text: &str) -> Option<u64> {
+    if text.is_empty() || !text.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    text.parse().ok()
+}
+
+fn scale(value: u64, factor: u64) -> Option<u64> {
+    value.checked_mul(factor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn refuses_zero_and_durations_over_a_week() {
+        assert!(check_seconds(0).is_err());
+        assert!(check_seconds(1).is_ok());
+        assert!(check_seconds(MAX_SECONDS).is_ok());
+        assert!(check_seconds(MAX_SECONDS + 1).is_err());
+        assert!(check_seconds(u64::MAX).is_err());
+    }
+
+    #[test]
+    fn reads_unit_strings() {
+        assert_eq!(parse_duration("90s"), Ok(90));
+        assert_eq!(parse_duration("7m30s"), Ok(450));
+        assert_eq!(parse_duration("1h15m"), Ok(4500));
+        assert_eq!(parse_duration("1h 15m"), Ok(4500));
+        assert_eq!(parse_duration(" 2M "), Ok(120));
+    }
+
+    #[test]
+    fn trailing_number_takes_the_next_smaller_unit() {
+        assert_eq!(parse_duration("7m30"), Ok(450));
+        assert_eq!(parse_duration("1h5"), Ok(3900));
+        assert!(parse_duration("30s5").is_err());
+    }
+
+    #[test]
+    fn reads_clock_strings_and_bare_minutes() {
+        assert_eq!(parse_duration("12:30"), Ok(750));
+        assert_eq!(parse_duration("1:15:00"), Ok(4500));
+        assert_eq!(parse_duration("25"), Ok(1500));
+    }
+
+    #[test]
+    fn refuses_malformed_input() {
+        for input in [
+            "", "   ", "12:75", "1:60:00", "1:2:3:4", ":30", "s", "5x", "30s5m", "5m5m",
+        ] {
+            assert!(parse_duration(input).is_err(), "{:?} was accepted", input);
+        }
+    }
+
+    #[test]
+    fn refuses_zero_and_numbers_that_overflow() {
+        assert!(parse_duration("0").is_err());
+        assert!(parse_duration("0m0s").is_err());
+        assert!(parse_duration("99999999999999999999s").is_err());
+        assert!(parse_duration("9999999999999999999m").is_err());
+        assert!(parse_duration("99999999999999999999").is_err());
+    }
+
+    #[test]
+    fn refuses_typed_durations_over_a_week() {
+        assert_eq!(parse_duration("168h"), Ok(MAX_SECONDS));
+        assert!(parse_duration("168h1s").is_err());
+        assert!(parse_duration("9999999999999999999s").is_err());
+    }
+}
@@ -1,26 +1,1074 @@
+mod audio;
+mod dial;
+mod duration;
+mod notify;
+mod overlay;
+mod persistence;
+mod pie;
+mod pin;
+mod pomodoro;
+mod presentation;
+mod presets;
+mod puck;
+mod sequence;
+mod settings;
+mod shortcuts;
+mod snap;
+mod stopwatch;
+mod timer;
+mod timers;
+mod tray;
+mod wall_clock;
+mod warning;
+mod window_layout;
+
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use audio::{AlarmSound, AudioPlayer, SoundConfig};
+use dial::DialLayout;
+use overlay::{Overlay, OverlayStatus};
+use pomodoro::PomodoroConfig;
+use presentation::MonitorInfo;
+use presets::WindowPreset;
+use puck::PuckAction;
+use settings::{Settings, SettingsStore};
+use shortcuts::{ShortcutAction, ShortcutConfig};
+use snap::SnapPosition;
+use tauri::{
+    AppHandle, DragDropEvent, Emitter, Manager, RunEvent, State, WebviewWindow, WindowEvent,
+};
+use timer::{TickEvent, TimerEngine, TimerMode, TimerState, TimerStatus};
+use timers::{Timer, TimerInfo, TimerRegistry};
+use tray::TrayAction;
+use warning::Warning;
+use window_layout::{WindowLayouts, WindowView};
+
 // Learn more about Tauri commands at https://tauri.app/develop/calling-rust/
 #[tauri::command]
 fn greet(name: &str) -> String {
     format!("Hello, {}! You've been greeted from Rust!", name)
 }
 
+/// The window presets, in the order of their `Cmd/Ctrl` + digit shortcuts.
+#[tauri::command]
+fn get_presets(settings: State<'_, SettingsStore>) -> Vec<WindowPreset> {
+    settings.get().presets
+}
+
+/// Resizes the window to a preset, in logical pixels.
+#[tauri::command]
+fn apply_preset(
+    app: AppHandle,
+    window: WebviewWindow,
+    settings: State<'_, SettingsStore>,
+    name: String,
+) -> Result<WindowPreset, String> {
+    let settings = settings.get();
+    let preset = presets::find(&settings.presets, &name)?;
+    presets::apply(&app, &window, preset)?;
+    Ok(preset.clone())
+}
+
+/// Moves the window to a corner or edge of its monitor.
+#[tauri::command]
+fn snap_window(
+    window: WebviewWindow,
+    settings: State<'_, SettingsStore>,
+    position: SnapPosition,
+) -> Result<(), String> {
+    snap::snap(&window, position, settings.get().snap_margin)
+}
+
+/// Moves the window on to the next monitor, in the same spot.
+#[tauri::command]
+fn move_to_next_monitor(
+    window: WebviewWindow,
+    settings: State<'_, SettingsStore>,
+) -> Result<(), String> {
+    snap::next_monitor(&window, settings.get().snap_margin)
+}
+
+/// Switches between the normal window and the frameless round puck.
+#[tauri::command]
+fn toggle_puck(
+    app: AppHandle,
+    window: WebviewWindow,
+    settings: State<'_, SettingsStore>,
+) -> Result<Settings, String> {
+    if window.label() != timers::MAIN {
+        return Err("Only the main window can become a puck".to_string());
+    }
+    let settings = settings.update(&app, |s| s.puck = !s.puck)?;
+    puck_changed(&app, &window, settings.puck)?;
+    Ok(settings)
+}
+
+/// The puck has no title bar; its menu is opened from the webview instead.
+#[tauri::command]
+fn show_puck_menu(window: WebviewWindow) -> Result<(), String> {
+    puck::show_menu(&window)
+}
+
+fn puck_changed(app: &AppHandle, window: &WebviewWindow, active: bool) -> Result<(), String> {
+    puck::apply(window, active)?;
+    if let Err(e) = app.emit("puck://changed", active) {
+        eprintln!("Failed to emit puck mode: {}", e);
+    }
+    Ok(())
+}
+
+fn handle_puck_action(window: &WebviewWindow, action: PuckAction) {
+    let result = match action {
+        PuckAction::Leave => {
+            let app = window.app_handle();
+            app.state::<SettingsStore>()
+                .update(app, |s| s.puck = false)
+                .and_then(|_| puck_changed(app, window, false))
+        }
+        PuckAction::Close => window
+            .close()
+            .map_err(|e| format!("Failed to close window: {}", e)),
+    };
+    if let Err(e) = result {
+        eprintln!("{}", e);
+    }
+}
+
+/// The monitors the dial can be presented on.
+#[tauri::command]
+fn list_monitors(window: WebviewWindow) -> Result<Vec<MonitorInfo>, String> {
+    presentation::monitors(&window)
+}
+
+/// Shows the dial fullscreen on one of the monitors from `list_monitors`,
+/// keeping this window on another one. Async, like every command that
+/// builds a window: a sync one would do it on the main thread, which
+/// deadlocks on Windows.
+#[tauri::command]
+async fn start_presentation(
+    app: AppHandle,
+    window: WebviewWindow,
+    monitor: usize,
+) -> Result<(), String> {
+    presentation::start(&app, &window, monitor)
+}
+
+#[tauri::command]
+fn stop_presentation(app: AppHandle) -> Result<bool, String> {
+    presentation::stop(&app)
+}
+
+/// The preset and label visibility to start with, as restored for the
+/// monitors connected now.
+#[tauri::command]
+fn get_window_view(layouts: State<'_, WindowLayouts>) -> WindowView {
+    layouts.view()
+}
+
+/// Remembers a preset or label change, along with the window's position and
+/// size, for the monitors connected now.
+#[tauri::command]
+fn set_window_view(
+    app: AppHandle,
+    window: WebviewWindow,
+    layouts: State<'_, WindowLayouts>,
+    view: WindowView,
+) {
+    // The windows of added timers come and go; only the main one's is kept
+    if window.label() == timers::MAIN {
+        layouts.set_view(&app, &window, view);
+    }
+}
+
+fn save_window_layout(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window(timers::MAIN) {
+        app.state::<WindowLayouts>().save(app, &window);
+    }
+}
+
+/// Adds a timer of its own, in a new window, to run alongside the others.
+/// Async so the window is built off the main thread, like in
+/// `start_presentation`.
+#[tauri::command]
+async fn create_timer(
+    app: AppHandle,
+    timers: State<'_, TimerRegistry>,
+    settings: State<'_, SettingsStore>,
+    title: Option<String>,
+) -> Result<TimerInfo, String> {
+    let timer = timers.add(title);
+    configure(&timer.engine, &settings.get());
+    if let Err(e) = timers::open_window(&app, &timer) {
+        timers.remove(&timer.id);
+        return Err(e);
+    }
+    tray::update(&app);
+    Ok(timer.info())
+}
+
+/// Every timer with its state, the main one first.
+#[tauri::command]
+fn list_timers(timers: State<'_, TimerRegistry>) -> Vec<TimerInfo> {
+    timers.all().iter().map(Timer::info).collect()
+}
+
+#[tauri::command]
+fn focus_timer(app: AppHandle, timers: State<'_, TimerRegistry>, id: String) -> Result<(), String> {
+    timers.get(&id)?;
+    timers::focus_window(&app, &id)
+}
+
+/// Stops a timer and closes its window. The main timer can't be closed.
+#[tauri::command]
+fn close_timer(app: AppHandle, timers: State<'_, TimerRegistry>, id: String) -> Result<(), String> {
+    if id == timers::MAIN {
+        return Err("The main timer can't be closed".to_string());
+    }
+    timers.get(&id)?;
+    forget_timer(&app, &id);
+    match app.get_webview_window(&id) {
+        Some(window) => window
+            .destroy()
+            .map_err(|e| format!("Failed to close timer window: {}", e)),
+        None => Ok(()),
+    }
+}
+
+// A timer goes along with its window, however that was closed.
+fn forget_timer(app: &AppHandle, id: &str) {
+    if let Some(timer) = app.state::<TimerRegistry>().remove(id) {
+        timer.engine.stop();
+        app.state::<AudioPlayer>().stop(&timer.id);
+        tray::update(app);
+    }
+}
+
+// Overtime and warnings are settings shared by every timer.
+fn configure(engine: &TimerEngine, settings: &Settings) {
+    engine.set_overtime(settings.overtime);
+    engine.set_warnings(settings.warnings.iter().map(|w| w.seconds_left));
+}
+
+/// Starts a countdown on the timer shown in the calling window, like every
+/// timer command.
+#[tauri::command]
+fn start_timer(
+    app: AppHandle,
+    window: WebviewWindow,
+    timers: State<'_, TimerRegistry>,
+    seconds: u64,
+    label: Option<String>,
+) -> Result<TimerState, String> {
+    let timer = timers.shown_in(window.label())?;
+    start_countdown(&app, &timer, seconds, label)
+}
+
+fn start_countdown(
+    app: &AppHandle,
+    timer: &Timer,
+    seconds: u64,
+    label: Option<String>,
+) -> Result<TimerState, String> {
+    // Zero just clears the timer
+    if seconds > 0 {
+        duration::check_seconds(seconds)?;
+    }
+    let (state, generation) = timer.engine.start(seconds, label)?;
+    timer_changed(app, timer, &state);
+    if state.status == TimerStatus::Running {
+        let remembered = app
+            .state::<SettingsStore>()
+            .update(app, |s| s.last_duration_seconds = Some(seconds));
+        if let Err(e) = remembered {
+            eprintln!("Failed to remember duration: {}", e);
+        }
+        spawn_countdown(app.clone(), timer.clone(), generation, &state);
+    }
+    Ok(state)
+}
+
+/// Starts a timer from typed input such as `90s`, `7m30s`, `1h15m` or `12:30`.
+#[tauri::command]
+fn start_timer_with_duration(
+    app: AppHandle,
+    window: WebviewWindow,
+    timers: State<'_, TimerRegistry>,
+    input: String,
+    label: Option<String>,
+) -> Result<TimerState, String> {
+    let seconds = duration::parse_duration(&input)?;
+    start_timer(app, window, timers, seconds, label)
+}
+
+/// Counts down to a wall-clock time such as `14:30`, optionally on a date
+/// (`2026-03-01`) and in a time zone (`Europe/Berlin`).
+#[tauri::command]
+fn start_timer_until(
+    app: AppHandle,
+    window: WebviewWindow,
+    timers: State<'_, TimerRegistry>,
+    time: String,
+    date: Option<String>,
+    time_zone: Option<String>,
+    label: Option<String>,
+) -> Result<TimerState, String> {
+    let timer = timers.shown_in(window.label())?;
+    let target = wall_clock::resolve(&time, date.as_deref(), time_zone.as_deref())?;
+    let (state, generation) = timer
+        .engine
+        .start_until(target.remaining, label, target.display)?;
+    timer_changed(&app, &timer, &state);
+    if state.status == TimerStatus::Running {
+        spawn_countdown(app, timer, generation, &state);
+    }
+    Ok(state)
+}
+
+/// Starts a Pomodoro session using the phase lengths from the settings.
+#[tauri::command]
+fn start_pomodoro(
+    app: AppHandle,
+    window: WebviewWindow,
+    timers: State<'_, TimerRegistry>,
+    settings: State<'_, SettingsStore>,
+) -> Result<TimerState, String> {
+    let timer = timers.shown_in(window.label())?;
+    let (state, generation) = timer.engine.start_pomodoro(settings.get().pomodoro)?;
+    timer_changed(&app, &timer, &state);
+    spawn_countdown(app, timer, generation, &state);
+    Ok(state)
+}
+
+/// Loads a `.toml` or `.json` sequence file and starts running it.
 #[tauri::command]
-async fn resize_window(window: tauri::Window, width: f64, height: f64) -> Result<(), String> {
-    window
-        .set_size(tauri::Size::Physical(tauri::PhysicalSize {
-            width: width as u32,
-            height: height as u32,
-        }))
-        .map_err(|e| format!("Failed to resize window: {}", e))?;
+fn load_sequence(
+    app: AppHandle,
+    window: WebviewWindow,
+    timers: State<'_, TimerRegistry>,
+    path: String,
+) -> Result<TimerState, String> {
+    let timer = timers.shown_in(window.label())?;
+    start_sequence_file(&app, &timer, Path::new(&path))
+}
+
+fn start_sequence_file(app: &AppHandle, timer: &Timer, path: &Path) -> Result<TimerState, String> {
+    let sequence = sequence::load(path)?;
+    let (state, generation) = timer.engine.start_sequence(sequence)?;
+    timer_changed(app, timer, &state);
+    spawn_countdown(app.clone(), timer.clone(), generation, &state);
+    Ok(state)
+}
+
+// Dropping a sequence file onto a window runs it on that window's timer
+// straight away; dropping a sound file makes it the alarm.
+fn handle_file_drop(app: &AppHandle, window_label: &str, paths: &[PathBuf]) {
+    let Some(path) = paths.first() else {
+        return;
+    };
+    let result = if audio::is_sound_file(path) {
+        use_alarm_file(app, path)
+    } else {
+        app.state::<TimerRegistry>()
+            .shown_in(window_label)
+            .and_then(|timer| start_sequence_file(app, &timer, path))
+            .map(|_| ())
+    };
+    if let Err(e) = result {
+        if let Err(e) = app.emit("file://error", &e) {
+            eprintln!("Failed to emit file error: {}", e);
+        }
+    }
+}
+
+// Plays the new alarm once so it can be heard straight away.
+fn use_alarm_file(app: &AppHandle, path: &Path) -> Result<(), String> {
+    audio::check_file(path)?;
+    let settings = app.state::<SettingsStore>().update(app, |s| {
+        s.sound.alarm = AlarmSound::File(path.to_path_buf())
+    })?;
+    app.state::<AudioPlayer>()
+        .play_alarm(audio::PREVIEW, &settings.sound);
     Ok(())
 }
 
+/// Starts counting up from zero, replacing whatever was running.
+#[tauri::command]
+fn start_stopwatch(
+    app: AppHandle,
+    window: WebviewWindow,
+    timers: State<'_, TimerRegistry>,
+) -> Result<TimerState, String> {
+    let timer = timers.shown_in(window.label())?;
+    let (state, generation) = timer.engine.start_stopwatch();
+    timer_changed(&app, &timer, &state);
+    spawn_countdown(app, timer, generation, &state);
+    Ok(state)
+}
+
+#[tauri::command]
+fn record_lap(
+    app: AppHandle,
+    window: WebviewWindow,
+    timers: State<'_, TimerRegistry>,
+) -> Result<TimerState, String> {
+    let timer = timers.shown_in(window.label())?;
+    let state = timer.engine.record_lap();
+    timer_changed(&app, &timer, &state);
+    Ok(state)
+}
+
+#[tauri::command]
+fn stop_timer(
+    app: AppHandle,
+    window: WebviewWindow,
+    timers: State<'_, TimerRegistry>,
+) -> Result<TimerState, String> {
+    Ok(stop(&app, &timers.shown_in(window.label())?))
+}
+
+#[tauri::command]
+fn pause_timer(
+    app: AppHandle,
+    window: WebviewWindow,
+    timers: State<'_, TimerRegistry>,
+) -> Result<TimerState, String> {
+    Ok(pause(&app, &timers.shown_in(window.label())?))
+}
+
+#[tauri::command]
+fn resume_timer(
+    app: AppHandle,
+    window: WebviewWindow,
+    timers: State<'_, TimerRegistry>,
+) -> Result<TimerState, String> {
+    resume(&app, &timers.shown_in(window.label())?)
+}
+
+/// "+5 min" on a timer that ran out, or more time on one still counting.
+#[tauri::command]
+fn extend_timer(
+    app: AppHandle,
+    window: WebviewWindow,
+    timers: State<'_, TimerRegistry>,
+) -> Result<TimerState, String> {
+    extend(&app, &timers.shown_in(window.label())?)
+}
+
+/// Runs the last countdown or sequence again from the start.
+#[tauri::command]
+fn restart_timer(
+    app: AppHandle,
+    window: WebviewWindow,
+    timers: State<'_, TimerRegistry>,
+) -> Result<TimerState, String> {
+    restart(&app, &timers.shown_in(window.label())?)
+}
+
+#[tauri::command]
+fn get_timer_state(
+    window: WebviewWindow,
+    timers: State<'_, TimerRegistry>,
+) -> Result<TimerState, String> {
+    Ok(timers.shown_in(window.label())?.engine.state())
+}
+
+fn stop(app: &AppHandle, timer: &Timer) -> TimerState {
+    let state = timer.engine.stop();
+    timer_changed(app, timer, &state);
+    state
+}
+
+fn pause(app: &AppHandle, timer: &Timer) -> TimerState {
+    let state = timer.engine.pause();
+    timer_changed(app, timer, &state);
+    state
+}
+
+fn resume(app: &AppHandle, timer: &Timer) -> Result<TimerState, String> {
+    let (state, generation) = timer.engine.resume()?;
+    timer_changed(app, timer, &state);
+    if let Some(generation) = generation {
+        spawn_countdown(app.clone(), timer.clone(), generation, &state);
+    }
+    Ok(state)
+}
+
+fn extend(app: &AppHandle, timer: &Timer) -> Result<TimerState, String> {
+    let (state, generation) = timer.engine.extend(Duration::from_secs(5 * 60))?;
+    timer_changed(app, timer, &state);
+    if let Some(generation) = generation {
+        spawn_countdown(app.clone(), timer.clone(), generation, &state);
+    }
+    Ok(state)
+}
+
+// Pomodoro sessions and the stopwatch have nothing to restart; they are
+// left as they are.
+fn restart(app: &AppHandle, timer: &Timer) -> Result<TimerState, String> {
+    let Some(started) = timer.engine.restart() else {
+        return Ok(timer.engine.state());
+    };
+    let (state, generation) = started?;
+    timer_changed(app, timer, &state);
+    spawn_countdown(app.clone(), timer.clone(), generation, &state);
+    Ok(state)
+}
+
+#[tauri::command]
+fn get_settings(settings: State<'_, SettingsStore>) -> Settings {
+    settings.get()
+}
+
+/// Changes the Pomodoro phase lengths; a running session keeps its own.
+#[tauri::command]
+fn set_pomodoro_config(
+    app: AppHandle,
+    settings: State<'_, SettingsStore>,
+    config: PomodoroConfig,
+) -> Result<Settings, String> {
+    config.validate()?;
+    settings.update(&app, |s| s.pomodoro = config)
+}
+
+/// Switches overtime for plain countdowns on or off.
+#[tauri::command]
+fn toggle_overtime(
+    app: AppHandle,
+    timers: State<'_, TimerRegistry>,
+    settings: State<'_, SettingsStore>,
+) -> Result<Settings, String> {
+    let settings = settings.update(&app, |s| s.overtime = !s.overtime)?;
+    for timer in timers.all() {
+        configure(&timer.engine, &settings);
+    }
+    Ok(settings)
+}
+
+/// Replaces the pre-end warnings; they apply to every kind of timer.
+#[tauri::command]
+fn set_warnings(
+    app: AppHandle,
+    timers: State<'_, TimerRegistry>,
+    settings: State<'_, SettingsStore>,
+    warnings: Vec<Warning>,
+) -> Result<Settings, String> {
+    warning::validate(&warnings)?;
+    let settings = settings.update(&app, |s| s.warnings = warnings)?;
+    for timer in timers.all() {
+        configure(&timer.engine, &settings);
+    }
+    Ok(settings)
+}
+
+/// Keeps the window above all others, or lets it be covered again.
+#[tauri::command]
+fn toggle_always_on_top(
+    app: AppHandle,
+    settings: State<'_, SettingsStore>,
+) -> Result<Settings, String> {
+    let settings = settings.update(&app, |s| s.pin.always_on_top = !s.pin.always_on_top)?;
+    Ok(pin_changed(&app, settings))
+}
+
+/// Shows the window on every workspace or virtual desktop, or on one.
+#[tauri::command]
+fn toggle_all_workspaces(
+    app: AppHandle,
+    settings: State<'_, SettingsStore>,
+) -> Result<Settings, String> {
+    let settings = settings.update(&app, |s| s.pin.all_workspaces = !s.pin.all_workspaces)?;
+    Ok(pin_changed(&app, settings))
+}
+
+/// Limits pinning to while a timer is running, or lifts that limit.
+#[tauri::command]
+fn set_pin_only_while_running(
+    app: AppHandle,
+    settings: State<'_, SettingsStore>,
+    enabled: bool,
+) -> Result<Settings, String> {
+    let settings = settings.update(&app, |s| s.pin.only_while_running = enabled)?;
+    Ok(pin_changed(&app, settings))
+}
+
+// An overlay stays on top whatever the pinning says; the user's pinning
+// comes back once it is left.
+fn pin_changed(app: &AppHandle, settings: Settings) -> Settings {
+    if !app.state::<Overlay>().is_active() {
+        let status = app.state::<TimerRegistry>().main().engine.state().status;
+        pin::apply(app, &settings.pin, status);
+    }
+    settings
+}
+
+/// Switches overlay mode on or off. While it is on, clicks go through the
+/// window, so only the global shortcut can switch it off again.
+#[tauri::command]
+fn toggle_overlay(
+    app: AppHandle,
+    overlay: State<'_, Overlay>,
+    settings: State<'_, SettingsStore>,
+) -> Result<OverlayStatus, String> {
+    let settings = settings.get();
+    let status = overlay::set_active(&app, !overlay.is_active(), &settings.overlay)?;
+    if !status.active {
+        // Leaving the overlay brings the frame back, unless it's a puck
+        if let (true, Some(window)) = (settings.puck, app.get_webview_window(timers::MAIN)) {
+            puck::apply(&window, true)?;
+        }
+        pin_changed(&app, settings);
+    }
+    Ok(status)
+}
+
+#[tauri::command]
+fn set_overlay_opacity(
+    app: AppHandle,
+    settings: State<'_, SettingsStore>,
+    opacity: f64,
+) -> Result<Settings, String> {
+    let config = overlay::OverlayConfig { opacity };
+    config.validate()?;
+    let settings = settings.update(&app, |s| s.overlay = config)?;
+    overlay::emit(&app, &settings.overlay);
+    Ok(settings)
+}
+
+/// Replaces the global shortcuts. If one can't be registered, the previous
+/// ones are kept.
+#[tauri::command]
+fn set_shortcuts(
+    app: AppHandle,
+    settings: State<'_, SettingsStore>,
+    shortcuts: ShortcutConfig,
+) -> Result<Settings, String> {
+    shortcuts.validate()?;
+    if let Err(e) = shortcuts::register(&app, &shortcuts, handle_shortcut) {
+        // Put the saved ones back; `register` logs whatever still fails.
+        let _ = shortcuts::register(&app, &settings.get().shortcuts, handle_shortcut);
+        return Err(e);
+    }
+    settings.update(&app, |s| s.shortcuts = shortcuts)
+}
+
+/// Changes the alarm sound, its volume and repeat count, and the minute chime.
+#[tauri::command]
+fn set_sound_config(
+    app: AppHandle,
+    settings: State<'_, SettingsStore>,
+    config: SoundConfig,
+) -> Result<Settings, String> {
+    config.validate()?;
+    if let AlarmSound::File(path) = &config.alarm {
+        audio::check_file(path)?;
+    }
+    settings.update(&app, |s| s.sound = config)
+}
+
+/// Plays the alarm as it is currently set up.
+#[tauri::command]
+fn preview_alarm(audio: State<'_, AudioPlayer>, settings: State<'_, SettingsStore>) {
+    audio.play_alarm(audio::PREVIEW, &settings.get().sound);
+}
+
+#[tauri::command]
+fn get_dial_layout(settings: State<'_, SettingsStore>) -> DialLayout {
+    dial::layout(settings.get().dial_span_minutes)
+}
+
+/// Switches the dial to one of `dial::DIAL_SPANS` (in minutes).
+#[tauri::command]
+fn set_dial_span(
+    app: AppHandle,
+    settings: State<'_, SettingsStore>,
+    minutes: u32,
+) -> Result<DialLayout, String> {
+    dial::check_span(minutes)?;
+    let settings = settings.update(&app, |s| s.dial_span_minutes = minutes)?;
+    Ok(dial_changed(&app, &settings))
+}
+
+#[tauri::command]
+fn cycle_dial_span(
+    app: AppHandle,
+    settings: State<'_, SettingsStore>,
+) -> Result<DialLayout, String> {
+    let settings = settings.update(&app, |s| {
+        s.dial_span_minutes = dial::next_span(s.dial_span_minutes)
+    })?;
+    Ok(dial_changed(&app, &settings))
+}
+
+fn dial_changed(app: &AppHandle, settings: &Settings) -> DialLayout {
+    let layout = dial::layout(settings.dial_span_minutes);
+    if let Err(e) = app.emit("settings://dial", &layout) {
+        eprintln!("Failed to emit dial layout: {}", e);
+    }
+    tray::update(app);
+    layout
+}
+
+const TIMER_FILE: &str = "timer.json";
+
+// Held from taking the main timer's snapshot until it is on disk, so an
+// older snapshot from another thread can't be written over a newer one.
+static SAVING_TIMER: Mutex<()> = Mutex::new(());
+
+// Status changes go to the timer's windows, and the main timer's are written
+// to disk. Plain ticks don't need saving: the stored deadline already covers
+// them.
+fn timer_changed(app: &AppHandle, timer: &Timer, state: &TimerState) {
+    emit_tick(app, timer, state);
+    // Whatever the user did next with this timer, its alarm has been noticed.
+    if matches!(
+        state.status,
+        TimerStatus::Idle | TimerStatus::Running | TimerStatus::Paused
+    ) {
+        app.state::<AudioPlayer>().stop(&timer.id);
+    }
+    // Pinning is about the main window, and added timers aren't restored
+    if timer.id != timers::MAIN {
+        return;
+    }
+    let pin = app.state::<SettingsStore>().get().pin;
+    if pin.only_while_running && !app.state::<Overlay>().is_active() {
+        pin::apply(app, &pin, state.status);
+    }
+    let _saving = SAVING_TIMER.lock().unwrap();
+    let saved = timer.engine.saved();
+    if let Err(e) = persistence::write_json(app, TIMER_FILE, &saved) {
+        eprintln!("Failed to save timer: {}", e);
+    }
+}
+
+fn emit_tick(app: &AppHandle, timer: &Timer, state: &TimerState) {
+    if let Err(e) = timers::emit(app, &timer.id, "timer://tick", state) {
+        eprintln!("Failed to emit timer tick: {}", e);
+    }
+    tray::update(app);
+}
+
+// One thread per started countdown; it exits as soon as the engine moves on
+// to a newer generation (restart or stop). Each wake-up re-reads the
+// monotonic deadline, so oversleeping only delays a tick, never the timer.
+fn spawn_countdown(app: AppHandle, timer: Timer, generation: u64, state: &TimerState) {
+    let mut wait = timer::until_next_second(state);
+    thread::spawn(move || loop {
+        thread::sleep(wait);
+
+        let Some((state, event)) = timer.engine.tick(generation) else {
+            break;
+        };
+
+        match event {
+            TickEvent::Tick => {
+                emit_tick(&app, &timer, &state);
+                minute_chime(&app, &state);
+            }
+            TickEvent::Warning(seconds_left) => {
+                emit_tick(&app, &timer, &state);
+                timer_warning(&app, &timer, &state, seconds_left);
+            }
+            TickEvent::PhaseChanged => {
+                timer_changed(&app, &timer, &state);
+                play_alarm(&app, &timer);
+                if let Err(e) = timers::emit(&app, &timer.id, "timer://phase", &state) {
+                    eprintln!("Failed to emit timer phase: {}", e);
+                }
+            }
+            TickEvent::Overtime => timer_finished(&app, &timer, &state),
+            TickEvent::Finished => {
+                timer_finished(&app, &timer, &state);
+                break;
+            }
+        }
+        wait = timer::until_next_second(&state);
+    });
+}
+
+// Reaching zero counts as finishing, whether or not overtime follows.
+fn timer_finished(app: &AppHandle, timer: &Timer, state: &TimerState) {
+    timer_changed(app, timer, state);
+    if let Err(e) = timers::emit(app, &timer.id, "timer://finished", state) {
+        eprintln!("Failed to emit timer finished: {}", e);
+    }
+    notify::timer_finished(app, timer, state, handle_finished_action);
+    play_alarm(app, timer);
+}
+
+// Only the parts of a warning the user asked for. The minute chime gives
+// way to the warning's own sound.
+fn timer_warning(app: &AppHandle, timer: &Timer, state: &TimerState, seconds_left: u64) {
+    let settings = app.state::<SettingsStore>().get();
+    let Some(warning) = warning::find(&settings.warnings, seconds_left) else {
+        return;
+    };
+    if warning.sound {
+        app.state::<AudioPlayer>().play_warning(&settings.sound);
+    } else {
+        minute_chime(app, state);
+    }
+    if warning.notification {
+        notify::warning(app, timer, state, seconds_left);
+    }
+    if warning.flash {
+        if let Err(e) = timers::emit(app, &timer.id, "timer://warning", seconds_left) {
+            eprintln!("Failed to emit timer warning: {}", e);
+        }
+    }
+}
+
+fn play_alarm(app: &AppHandle, timer: &Timer) {
+    let settings = app.state::<SettingsStore>().get();
+    app.state::<AudioPlayer>()
+        .play_alarm(&timer.id, &settings.sound);
+}
+
+// Lands on a tick the dial flashes as it is crossed (see `highlightTick`
+// in the webview), when that tick is on a whole minute left.
+fn minute_chime(app: &AppHandle, state: &TimerState) {
+    if state.mode == TimerMode::Stopwatch || state.status != TimerStatus::Running {
+        return;
+    }
+    let settings = app.state::<SettingsStore>().get();
+    let every = dial::layout(settings.dial_span_minutes).chime_seconds();
+    if state.remaining_seconds > 0 && state.remaining_seconds.is_multiple_of(every) {
+        app.state::<AudioPlayer>()
+            .play_minute_chime(&settings.sound);
+    }
+}
+
+// The "+5 min" and "Restart" buttons of the finished notification, for a
+// timer that may have been closed since.
+fn handle_finished_action(app: &AppHandle, action: notify::FinishedAction, id: &str) {
+    let Ok(timer) = app.state::<TimerRegistry>().get(id) else {
+        return;
+    };
+    let result = match action {
+        notify::FinishedAction::Extend => extend(app, &timer),
+        notify::FinishedAction::Restart => restart(app, &timer),
+    };
+    if let Err(e) = result {
+        eprintln!("{}", e);
+    }
+}
+
+// Quick starts go to the main timer; everything else to the one the tray
+// icon shows.
+fn handle_tray_action(app: &AppHandle, action: TrayAction) {
+    let timers = app.state::<TimerRegistry>();
+    match action {
+        TrayAction::PauseResume => toggle_pause(app, &timers.foremost()),
+        TrayAction::Stop => {
+            stop(app, &timers.foremost());
+        }
+        TrayAction::Extend => {
+            if let Err(e) = extend(app, &timers.foremost()) {
+                eprintln!("{}", e);
+            }
+        }
+        TrayAction::Restart => {
+            if let Err(e) = restart(app, &timers.foremost()) {
+                eprintln!("{}", e);
+            }
+        }
+        TrayAction::ToggleWindow => toggle_main_window(app),
+        TrayAction::QuickStart(minutes) => {
+            if let Err(e) = start_countdown(app, &timers.main(), minutes * 60, None) {
+                eprintln!("{}", e);
+            }
+        }
+        TrayAction::FocusTimer(id) => {
+            if let Err(e) = timers::focus_window(app, &id) {
+                eprintln!("{}", e);
+            }
+        }
+    }
+}
+
+fn handle_shortcut(app: &AppHandle, action: ShortcutAction) {
+    let timers = app.state::<TimerRegistry>();
+    match action {
+        ShortcutAction::StartLast => {
+            if let Some(seconds) = app.state::<SettingsStore>().get().last_duration_seconds {
+                if let Err(e) = start_countdown(app, &timers.main(), seconds, None) {
+                    eprintln!("{}", e);
+                }
+            }
+        }
+        ShortcutAction::PauseResume => toggle_pause(app, &timers.foremost()),
+        ShortcutAction::Stop => {
+            stop(app, &timers.foremost());
+        }
+        ShortcutAction::ToggleWindow => toggle_main_window(app),
+        ShortcutAction::ToggleAlwaysOnTop => {
+            if let Err(e) = toggle_always_on_top(app.clone(), app.state()) {
+                eprintln!("{}", e);
+            }
+        }
+        ShortcutAction::ToggleAllWorkspaces => {
+            if let Err(e) = toggle_all_workspaces(app.clone(), app.state()) {
+                eprintln!("{}", e);
+            }
+        }
+        ShortcutAction::ToggleOverlay => {
+            if let Err(e) = toggle_overlay(app.clone(), app.state(), app.state()) {
+                eprintln!("{}", e);
+            }
+        }
+    }
+}
+
+fn toggle_pause(app: &AppHandle, timer: &Timer) {
+    if timer.engine.state().status == TimerStatus::Paused {
+        if let Err(e) = resume(app, timer) {
+            eprintln!("{}", e);
+        }
+    } else {
+        pause(app, timer);
+    }
+}
+
+fn toggle_main_window(app: &AppHandle) {
+    let Some(window) = app.get_webview_window(timers::MAIN) else {
+        return;
+    };
+    let result = if window.is_visible().unwrap_or(false) {
+        window.hide()
+    } else {
+        window.show().and_then(|_| window.set_focus())
+    };
+    if let Err(e) = result {
+        eprintln!("Failed to show or hide window: {}", e);
+    }
+}
+
+// Bring back the timer from the last run, still counting if its deadline
+// hasn't passed yet.
+fn restore_timer(app: &AppHandle) {
+    let Some(saved) = persistence::read_json(app, TIMER_FILE) else {
+        return;
+    };
+
+    let timer = app.state::<TimerRegistry>().main();
+    let (state, generation) = match timer.engine.restore(saved) {
+        Ok(restored) => restored,
+        Err(e) => {
+            eprintln!("Failed to restore timer: {}", e);
+            return;
+        }
+    };
+    timer_changed(app, &timer, &state);
+    if let Some(generation) = generation {
+        spawn_countdown(app.clone(), timer, generation, &state);
+    }
+}
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
-        .invoke_handler(tauri::generate_handler![greet, resize_window])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .plugin(tauri_plugin_notification::init())
+        .plugin(tauri_plugin_global_shortcut::Builder::new().build())
+        .manage(TimerRegistry::new())
+        .manage(AudioPlayer::new())
+        .manage(Overlay::default())
+        .setup(|app| {
+            let settings = SettingsStore::load(app.handle());
+            let layouts = WindowLayouts::load(app.handle());
+            if let Some(window) = app.get_webview_window(timers::MAIN) {
+                layouts.restore(&window);
+                puck::init(&window, handle_puck_action);
+                if settings.get().puck {
+                    if let Err(e) = puck::apply(&window, true) {
+                        eprintln!("{}", e);
+                    }
+                }
+            }
+            app.manage(layouts);
+            configure(&app.state::<TimerRegistry>().main().engine, &settings.get());
+            app.manage(settings);
+            if let Err(e) = tray::init(app.handle(), handle_tray_action) {
+                eprintln!("{}", e);
+            }
+            let shortcuts = app.state::<SettingsStore>().get().shortcuts;
+            // Shortcuts that can't be had are logged; the app works without them.
+            let _ = shortcuts::register(app.handle(), &shortcuts, handle_shortcut);
+            restore_timer(app.handle());
+            pin_changed(app.handle(), app.state::<SettingsStore>().get());
+            Ok(())
+        })
+        .on_window_event(|window, event| match event {
+            WindowEvent::DragDrop(DragDropEvent::Drop { paths, .. }) => {
+                handle_file_drop(window.app_handle(), window.label(), paths);
+            }
+            WindowEvent::CloseRequested { api, .. } if window.label() == timers::MAIN => {
+                save_window_layout(window.app_handle());
+                // The timer keeps running in the tray; "Quit" there ends the app.
+                if tray::is_shown(window.app_handle()) {
+                    api.prevent_close();
+                    if let Err(e) = window.hide() {
+                        eprintln!("Failed to hide window: {}", e);
+                    }
+                }
+            }
+            WindowEvent::Destroyed => forget_timer(window.app_handle(), window.label()),
+            _ => {}
+        })
+        .invoke_handler(tauri::generate_handler![
+            greet,
+            get_presets,
+            apply_preset,
+            toggle_puck,
+            show_puck_menu,
+            list_monitors,
+            start_presentation,
+            stop_presentation,
+            snap_window,
+            move_to_next_monitor,
+            get_window_view,
+            set_window_view,
+            create_timer,
+            list_timers,
+            focus_timer,
+            close_timer,
+            start_timer,
+            start_timer_with_duration,
+            start_timer_until,
+            start_pomodoro,
+            load_sequence,
+            start_stopwatch,
+            record_lap,
+            stop_timer,
+            pause_timer,
+            resume_timer,
+            extend_timer,
+            restart_timer,
+            get_timer_state,
+            get_settings,
+            set_pomodoro_config,
+            toggle_overtime,
+            set_warnings,
+            set_shortcuts,
+            toggle_always_on_top,
+            toggle_all_workspaces,
+            set_pin_only_while_running,
+            toggle_overlay,
+            set_overlay_opacity,
+            set_sound_config,
+            preview_alarm,
+            get_dial_layout,
+            set_dial_span,
+            cycle_dial_span
+        ])
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            if let RunEvent::ExitRequested { .. } = event {
+                save_window_layout(app);
+            }
+        });
 }
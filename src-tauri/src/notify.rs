@@ -0,0 +1,131 @@
+use std::thread;
+
+use tauri::AppHandle;
+use tauri_plugin_notification::NotificationExt;
+
+use crate::timer::{TimerState, TimerStatus};
+use crate::timers::{self, Timer};
+
+const EXTEND_ACTION: &str = "extend";
+const RESTART_ACTION: &str = "restart";
+
+/// A button pressed on a "finished" notification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FinishedAction {
+    /// "+5 min"
+    Extend,
+    Restart,
+}
+
+/// Tells the user a timer reached zero, even with the window out of sight,
+/// with "+5 min" and "Restart" buttons whose presses go to `on_action` along
+/// with the timer's id. The plugin only puts buttons on notifications on
+/// mobile, so this one goes through notify-rust, which the plugin uses
+/// underneath on the desktop. Notification servers that drop the buttons
+/// leave the same two in the tray menu and the timer's window.
+pub fn timer_finished(
+    app: &AppHandle,
+    timer: &Timer,
+    state: &TimerState,
+    on_action: impl FnOnce(&AppHandle, FinishedAction, &str) + Send + 'static,
+) {
+    let title = match &state.label {
+        Some(label) => label,
+        None if timer.id == timers::MAIN => "Time's up",
+        None => &timer.title,
+    };
+    let body = if state.status == TimerStatus::Overtime {
+        "Counting overtime".to_string()
+    } else if let Some(target) = &state.target {
+        format!("It's {}", target)
+    } else if state.sequence.is_some() {
+        "Sequence finished".to_string()
+    } else {
+        format!("{} timer finished", describe(state.duration_seconds))
+    };
+
+    let mut notification = notify_rust::Notification::new();
+    notification
+        .summary(title)
+        .body(&body)
+        .auto_icon()
+        .action(EXTEND_ACTION, "+5 min")
+        .action(RESTART_ACTION, "Restart");
+    // Attributed to the app like the plugin's own notifications, once it is
+    // installed under its identifier
+    #[cfg(windows)]
+    if !tauri::is_dev() {
+        notification.app_id(&app.config().identifier);
+    }
+    #[cfg(target_os = "macos")]
+    let _ = notify_rust::set_application(if tauri::is_dev() {
+        "com.apple.Terminal"
+    } else {
+        &app.config().identifier
+    });
+
+    // Waiting for a button blocks until the notification goes away
+    let app = app.clone();
+    let id = timer.id.clone();
+    thread::spawn(move || match notification.show() {
+        Ok(shown) => shown.wait_for_action(|action| {
+            let action = match action {
+                EXTEND_ACTION => FinishedAction::Extend,
+                RESTART_ACTION => FinishedAction::Restart,
+                _ => return,
+            };
+            on_action(&app, action, &id);
+        }),
+        Err(e) => eprintln!("Failed to show notification: {}", e),
+    });
+}
+
+/// A pre-end warning; no buttons, there's nothing to decide yet.
+pub fn warning(app: &AppHandle, timer: &Timer, state: &TimerState, seconds_left: u64) {
+    let title = state.label.as_deref().unwrap_or(&timer.title);
+    let result = app
+        .notification()
+        .builder()
+        .title(title)
+        .body(format!("{} left", describe(seconds_left)))
+        .show();
+    if let Err(e) = result {
+        eprintln!("Failed to show notification: {}", e);
+    }
+}
+
+// "25 min", "1 h 30 min" or "7 min 30 s", the way durations are usually
+// typed.
+fn describe(seconds: u64) -> String {
+    let parts = [
+        (seconds / 3600, "h"),
+        (seconds % 3600 / 60, "min"),
+        (seconds % 60, "s"),
+    ]
+    .into_iter()
+    .filter(|&(amount, _)| amount > 0)
+    .map(|(amount, unit)| format!("{} {}", amount, unit))
+    .collect::<Vec<_>>();
+    if parts.is_empty() {
+        "0 s".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describes_durations_in_the_units_they_are_typed_in() {
+        assert_eq!(describe(90), "1 min 30 s");
+        assert_eq!(describe(450), "7 min 30 s");
+        assert_eq!(describe(45), "45 s");
+        assert_eq!(describe(25 * 60), "25 min");
+        assert_eq!(describe(2 * 3600), "2 h");
+        assert_eq!(describe(5400), "1 h 30 min");
+        assert_eq!(describe(3605), "1 h 5 s");
+        assert_eq!(describe(3725), "1 h 2 min 5 s");
+    }
+}
@@ -0,0 +1,89 @@
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::timers::MAIN;
+
+/// Overlay options, part of the settings file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct OverlayConfig {
+    /// How solid the dial looks while it is an overlay, from 0.1 to 1.0.
+    pub opacity: f64,
+}
+
+impl Default for OverlayConfig {
+    fn default() -> Self {
+        Self { opacity: 0.6 }
+    }
+}
+
+impl OverlayConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0.1..=1.0).contains(&self.opacity) {
+            return Err("Overlay opacity must be between 0.1 and 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Sent to the webview as `overlay://changed`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OverlayStatus {
+    pub active: bool,
+    pub opacity: f64,
+}
+
+/// Managed as Tauri state. Overlay mode is never saved: starting up with a
+/// window that can't be clicked would be a surprise.
+#[derive(Default)]
+pub struct Overlay {
+    active: Mutex<bool>,
+}
+
+impl Overlay {
+    pub fn is_active(&self) -> bool {
+        *self.active.lock().unwrap()
+    }
+}
+
+/// Turns the main window into a frameless, always-on-top overlay that lets
+/// clicks through to whatever is below it, or back into a normal window.
+/// Pinning is left on when leaving; the caller puts back the user's own.
+pub fn set_active(
+    app: &AppHandle,
+    active: bool,
+    config: &OverlayConfig,
+) -> Result<OverlayStatus, String> {
+    let window = app
+        .get_webview_window(MAIN)
+        .ok_or("Failed to find the main window")?;
+    let result = if active {
+        window
+            .set_decorations(false)
+            .and_then(|_| window.set_always_on_top(true))
+            .and_then(|_| window.set_ignore_cursor_events(true))
+    } else {
+        window
+            .set_ignore_cursor_events(false)
+            .and_then(|_| window.set_decorations(true))
+    };
+    result.map_err(|e| format!("Failed to switch overlay mode: {}", e))?;
+
+    *app.state::<Overlay>().active.lock().unwrap() = active;
+    Ok(emit(app, config))
+}
+
+/// Tells the webview whether to draw itself as an overlay, and how solid.
+pub fn emit(app: &AppHandle, config: &OverlayConfig) -> OverlayStatus {
+    let status = OverlayStatus {
+        active: app.state::<Overlay>().is_active(),
+        opacity: config.opacity,
+    };
+    if let Err(e) = app.emit("overlay://changed", &status) {
+        eprintln!("Failed to emit overlay status: {}", e);
+    }
+    status
+}
@@ -0,0 +1,51 @@
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+// Countdown threads and commands save at the same time; one write at a time
+// keeps them from renaming each other's temp file away.
+static WRITING: Mutex<()> = Mutex::new(());
+
+/// Reads `file` from the app data directory. A missing or unreadable file
+/// is treated as "nothing saved" rather than an error.
+pub fn read_json<T: DeserializeOwned>(app: &AppHandle, file: &str) -> Option<T> {
+    let path = data_path(app, file).ok()?;
+    let contents = fs::read_to_string(&path).ok()?;
+    match serde_json::from_str(&contents) {
+        Ok(value) => Some(value),
+        Err(e) => {
+            eprintln!("Ignoring unreadable {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+/// Writes `value` to `file` in the app data directory. The file is written
+/// next to its final location and then renamed, so a crash mid-write never
+/// leaves a truncated file behind.
+pub fn write_json<T: Serialize>(app: &AppHandle, file: &str, value: &T) -> Result<(), String> {
+    let path = data_path(app, file)?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+
+    let contents = serde_json::to_string_pretty(value)
+        .map_err(|e| format!("Failed to serialize {}: {}", file, e))?;
+    let _writing = WRITING.lock().unwrap();
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, contents).map_err(|e| format!("Failed to write {}: {}", tmp.display(), e))?;
+    fs::rename(&tmp, &path).map_err(|e| format!("Failed to replace {}: {}", path.display(), e))?;
+    Ok(())
+}
+
+fn data_path(app: &AppHandle, file: &str) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map(|dir| dir.join(file))
+        .map_err(|e| format!("Failed to resolve app data directory: {}", e))
+}
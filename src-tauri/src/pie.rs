@@ -0,0 +1,260 @@
+use std::f32::consts::TAU;
+
+use crate::pomodoro::PomodoroPhase;
+use crate::timer::{TimerMode, TimerState, TimerStatus};
+
+/// Width and height of the tray icon, in pixels. The panel scales it down.
+pub const ICON_SIZE: u32 = 64;
+
+// Each pixel is sampled on a 4x4 grid so the edges come out smooth.
+const SUBSAMPLES: u32 = 4;
+
+type Rgb = [u8; 3];
+
+const WORK: Rgb = [0xef, 0x44, 0x44];
+const SHORT_BREAK: Rgb = [0x22, 0xc5, 0x5e];
+const LONG_BREAK: Rgb = [0x3b, 0x82, 0xf6];
+const OVERTIME: Rgb = [0xf5, 0x9e, 0x0b];
+const FACE: Rgb = [0xff, 0xff, 0xff];
+const RIM: Rgb = [0x1f, 0x29, 0x37];
+
+/// What the dial shows, rounded to whole degrees: the tray only needs a
+/// new image when this changes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sector {
+    /// 0 to 360, clockwise from twelve o'clock.
+    degrees: u16,
+    color: Rgb,
+    /// Paused timers are drawn faded.
+    faded: bool,
+}
+
+/// An empty dial, as when no timer is set.
+impl Default for Sector {
+    fn default() -> Self {
+        Self {
+            degrees: 0,
+            color: WORK,
+            faded: false,
+        }
+    }
+}
+
+/// The red sector from the dial for `state`, on a dial spanning
+/// `span_seconds`: remaining time for countdowns, elapsed time or overrun
+/// (going round again every span) for the stopwatch and overtime.
+pub fn sector(state: &TimerState, span_seconds: u64) -> Sector {
+    let span_seconds = span_seconds.max(1);
+    let overtime = state.status == TimerStatus::Overtime || state.overrun_ms > 0;
+    let seconds = if state.mode == TimerMode::Stopwatch {
+        state.elapsed_ms / 1000 % span_seconds
+    } else if overtime {
+        state.overrun_ms / 1000 % span_seconds
+    } else {
+        state.remaining_seconds.min(span_seconds)
+    };
+    let color = match state.pomodoro.as_ref().map(|p| p.phase) {
+        _ if overtime => OVERTIME,
+        Some(PomodoroPhase::ShortBreak) => SHORT_BREAK,
+        Some(PomodoroPhase::LongBreak) => LONG_BREAK,
+        _ => WORK,
+    };
+    Sector {
+        degrees: (seconds * 360 / span_seconds) as u16,
+        color,
+        faded: state.status == TimerStatus::Paused,
+    }
+}
+
+/// Draws the dial face with `sector` on it as `ICON_SIZE` square RGBA
+/// pixels.
+pub fn render(sector: &Sector) -> Vec<u8> {
+    let size = ICON_SIZE as f32;
+    let center = size / 2.0;
+    let face_radius = size * 0.48;
+    let rim_width = size * 0.06;
+    // The same proportion as the sector inside the dial in the window
+    let sector_radius = face_radius * 160.0 / 180.0;
+    let sweep = f32::from(sector.degrees) / 360.0 * TAU;
+    let sector_color = if sector.faded {
+        blend(sector.color, FACE, 0.5)
+    } else {
+        sector.color
+    };
+
+    let mut pixels = Vec::with_capacity((ICON_SIZE * ICON_SIZE * 4) as usize);
+    for y in 0..ICON_SIZE {
+        for x in 0..ICON_SIZE {
+            // Colors weighted by coverage, summed over the subsamples
+            let mut sum = [0.0f32; 4];
+            for sy in 0..SUBSAMPLES {
+                for sx in 0..SUBSAMPLES {
+                    let dx = x as f32 + (sx as f32 + 0.5) / SUBSAMPLES as f32 - center;
+                    let dy = y as f32 + (sy as f32 + 0.5) / SUBSAMPLES as f32 - center;
+                    let distance = dx.hypot(dy);
+                    if distance > face_radius {
+                        continue;
+                    }
+                    // Clockwise from twelve o'clock, like the dial
+                    let angle = dx.atan2(-dy).rem_euclid(TAU);
+                    let color = if distance > face_radius - rim_width {
+                        RIM
+                    } else if distance <= sector_radius && angle < sweep {
+                        sector_color
+                    } else {
+                        FACE
+                    };
+                    for (total, channel) in sum.iter_mut().zip(color) {
+                        *total += f32::from(channel);
+                    }
+                    sum[3] += 255.0;
+                }
+            }
+
+            let samples = (SUBSAMPLES * SUBSAMPLES) as f32;
+            let alpha = sum[3] / samples;
+            for total in &sum[..3] {
+                let channel = if alpha > 0.0 {
+                    total / sum[3] * 255.0
+                } else {
+                    0.0
+                };
+                pixels.push(channel.round() as u8);
+            }
+            pixels.push(alpha.round() as u8);
+        }
+    }
+    pixels
+}
+
+fn blend(color: Rgb, other: Rgb, amount: f32) -> Rgb {
+    let mix = |a: u8, b: u8| (f32::from(a) * (1.0 - amount) + f32::from(b) * amount).round() as u8;
+    [
+        mix(color[0], other[0]),
+        mix(color[1], other[1]),
+        mix(color[2], other[2]),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pomodoro::PomodoroStatus;
+
+    fn state(status: TimerStatus, mode: TimerMode) -> TimerState {
+        TimerState {
+            status,
+            mode,
+            duration_seconds: 0,
+            remaining_seconds: 0,
+            remaining_ms: 0,
+            elapsed_ms: 0,
+            overrun_ms: 0,
+            label: None,
+            target: None,
+            finished_while_away: false,
+            pomodoro: None,
+            sequence: None,
+            stopwatch: None,
+        }
+    }
+
+    fn countdown(remaining_seconds: u64) -> TimerState {
+        TimerState {
+            remaining_seconds,
+            remaining_ms: remaining_seconds * 1000,
+            ..state(TimerStatus::Running, TimerMode::Countdown)
+        }
+    }
+
+    fn pomodoro(phase: PomodoroPhase) -> TimerState {
+        TimerState {
+            pomodoro: Some(PomodoroStatus { phase, cycle: 1 }),
+            ..state(TimerStatus::Running, TimerMode::Pomodoro)
+        }
+    }
+
+    // RGBA of the pixel at `x`, `y`
+    fn pixel(pixels: &[u8], x: u32, y: u32) -> [u8; 4] {
+        let start = ((y * ICON_SIZE + x) * 4) as usize;
+        pixels[start..start + 4].try_into().unwrap()
+    }
+
+    #[test]
+    fn remaining_time_is_a_share_of_the_span() {
+        assert_eq!(sector(&countdown(15 * 60), 3600).degrees, 90);
+        assert_eq!(sector(&countdown(0), 3600).degrees, 0);
+    }
+
+    #[test]
+    fn remaining_time_longer_than_the_span_fills_the_dial() {
+        assert_eq!(sector(&countdown(2 * 3600), 3600).degrees, 360);
+        assert_eq!(sector(&countdown(3600), 3600).degrees, 360);
+    }
+
+    #[test]
+    fn the_stopwatch_goes_round_again_every_span() {
+        let mut stopwatch = state(TimerStatus::Running, TimerMode::Stopwatch);
+        stopwatch.elapsed_ms = 75 * 60 * 1000;
+        assert_eq!(sector(&stopwatch, 3600).degrees, 90);
+        stopwatch.elapsed_ms = 3600 * 1000;
+        assert_eq!(sector(&stopwatch, 3600).degrees, 0);
+    }
+
+    #[test]
+    fn overtime_shows_the_overrun_in_amber_going_round_again() {
+        let mut overtime = state(TimerStatus::Overtime, TimerMode::Countdown);
+        overtime.overrun_ms = 65 * 60 * 1000;
+        let drawn = sector(&overtime, 3600);
+        assert_eq!(drawn.degrees, 30);
+        assert_eq!(drawn.color, OVERTIME);
+
+        // Stopped in overtime, the overrun stays on the dial
+        overtime.status = TimerStatus::Finished;
+        assert_eq!(sector(&overtime, 3600).color, OVERTIME);
+    }
+
+    #[test]
+    fn pomodoro_phases_have_their_own_colors() {
+        assert_eq!(sector(&pomodoro(PomodoroPhase::Work), 3600).color, WORK);
+        assert_eq!(
+            sector(&pomodoro(PomodoroPhase::ShortBreak), 3600).color,
+            SHORT_BREAK
+        );
+        assert_eq!(
+            sector(&pomodoro(PomodoroPhase::LongBreak), 3600).color,
+            LONG_BREAK
+        );
+        assert_eq!(sector(&countdown(60), 3600).color, WORK);
+    }
+
+    #[test]
+    fn a_paused_timer_is_drawn_faded() {
+        let mut paused = countdown(15 * 60);
+        assert!(!sector(&paused, 3600).faded);
+        paused.status = TimerStatus::Paused;
+        let drawn = sector(&paused, 3600);
+        assert!(drawn.faded);
+
+        // Inside the sector, between twelve and three o'clock
+        let faded = pixel(&render(&drawn), 40, 20);
+        let [r, g, b] = blend(WORK, FACE, 0.5);
+        assert_eq!(faded, [r, g, b, 255]);
+    }
+
+    #[test]
+    fn render_draws_the_sector_on_a_round_face() {
+        let drawn = sector(&countdown(15 * 60), 3600);
+        let pixels = render(&drawn);
+        assert_eq!(pixels.len(), (ICON_SIZE * ICON_SIZE * 4) as usize);
+
+        // The corners are outside the dial and see-through
+        assert_eq!(pixel(&pixels, 0, 0)[3], 0);
+        assert_eq!(pixel(&pixels, ICON_SIZE - 1, ICON_SIZE - 1)[3], 0);
+        // The sector runs clockwise from twelve o'clock; the rest is face
+        let [r, g, b] = WORK;
+        assert_eq!(pixel(&pixels, 40, 20), [r, g, b, 255]);
+        let [r, g, b] = FACE;
+        assert_eq!(pixel(&pixels, 20, 40), [r, g, b, 255]);
+    }
+}
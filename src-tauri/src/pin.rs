@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::timer::TimerStatus;
+use crate::timers::MAIN;
+
+/// Keeping the window in sight, part of the settings file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PinConfig {
+    /// Above all other windows.
+    pub always_on_top: bool,
+    /// On every workspace or virtual desktop.
+    pub all_workspaces: bool,
+    /// Pin only while a timer is running or paused, and let the window drop
+    /// back behind others once it is done.
+    pub only_while_running: bool,
+}
+
+/// Pins or unpins the main window as `config` asks for a timer in `status`.
+pub fn apply(app: &AppHandle, config: &PinConfig, status: TimerStatus) {
+    let Some(window) = app.get_webview_window(MAIN) else {
+        return;
+    };
+    let active = matches!(
+        status,
+        TimerStatus::Running | TimerStatus::Paused | TimerStatus::Overtime
+    );
+    let allowed = active || !config.only_while_running;
+    let result = window
+        .set_always_on_top(allowed && config.always_on_top)
+        .and_then(|_| window.set_visible_on_all_workspaces(allowed && config.all_workspaces));
+    if let Err(e) = result {
+        eprintln!("Failed to pin window: {}", e);
+    }
+}
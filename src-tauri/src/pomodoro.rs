@@ -0,0 +1,213 @@
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::duration::MAX_SECONDS;
+
+/// Phase lengths for Pomodoro mode, part of the settings file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PomodoroConfig {
+    pub work_minutes: u32,
+    pub short_break_minutes: u32,
+    pub long_break_minutes: u32,
+    /// A long break replaces the short one after every N work phases.
+    pub long_break_every: u32,
+}
+
+impl Default for PomodoroConfig {
+    fn default() -> Self {
+        Self {
+            work_minutes: 25,
+            short_break_minutes: 5,
+            long_break_minutes: 15,
+            long_break_every: 4,
+        }
+    }
+}
+
+impl PomodoroConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        let phases = [
+            self.work_minutes,
+            self.short_break_minutes,
+            self.long_break_minutes,
+        ];
+        if phases.contains(&0) {
+            return Err("Pomodoro phases must be at least one minute long".to_string());
+        }
+        // Like any other timer, a phase runs a week at most
+        let longest = MAX_SECONDS / 60;
+        if phases.iter().any(|&minutes| u64::from(minutes) > longest) {
+            return Err(format!(
+                "Pomodoro phases must be at most {} minutes long",
+                longest
+            ));
+        }
+        if self.long_break_every == 0 {
+            return Err("Long break interval must be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PomodoroPhase {
+    Work,
+    ShortBreak,
+    LongBreak,
+}
+
+/// Where a Pomodoro session stands, sent along with the timer state.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PomodoroRun {
+    pub config: PomodoroConfig,
+    pub phase: PomodoroPhase,
+    /// Work phases completed so far in this session.
+    pub completed: u32,
+}
+
+impl PomodoroRun {
+    pub fn new(config: PomodoroConfig) -> Self {
+        Self {
+            config,
+            phase: PomodoroPhase::Work,
+            completed: 0,
+        }
+    }
+
+    /// 1-based number of the cycle in progress; a break belongs to the work
+    /// phase before it.
+    pub fn cycle(&self) -> u32 {
+        match self.phase {
+            PomodoroPhase::Work => self.completed + 1,
+            PomodoroPhase::ShortBreak | PomodoroPhase::LongBreak => self.completed,
+        }
+    }
+
+    pub fn phase_duration(&self) -> Duration {
+        let minutes = match self.phase {
+            PomodoroPhase::Work => self.config.work_minutes,
+            PomodoroPhase::ShortBreak => self.config.short_break_minutes,
+            PomodoroPhase::LongBreak => self.config.long_break_minutes,
+        };
+        Duration::from_secs(u64::from(minutes) * 60)
+    }
+
+    pub fn status(&self) -> PomodoroStatus {
+        PomodoroStatus {
+            phase: self.phase,
+            cycle: self.cycle(),
+        }
+    }
+
+    /// Moves on to the phase that follows the current one.
+    pub fn advance(&mut self) {
+        self.phase = match self.phase {
+            PomodoroPhase::Work => {
+                self.completed += 1;
+                if self
+                    .completed
+                    .is_multiple_of(self.config.long_break_every.max(1))
+                {
+                    PomodoroPhase::LongBreak
+                } else {
+                    PomodoroPhase::ShortBreak
+                }
+            }
+            PomodoroPhase::ShortBreak | PomodoroPhase::LongBreak => PomodoroPhase::Work,
+        };
+    }
+}
+
+/// The part of a session the webview shows: sector color and cycle counter.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PomodoroStatus {
+    pub phase: PomodoroPhase,
+    pub cycle: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(long_break_every: u32) -> PomodoroConfig {
+        PomodoroConfig {
+            long_break_every,
+            ..PomodoroConfig::default()
+        }
+    }
+
+    #[test]
+    fn long_break_replaces_every_nth_short_break() {
+        let mut run = PomodoroRun::new(config(2));
+        let mut phases = vec![run.phase];
+        for _ in 0..4 {
+            run.advance();
+            phases.push(run.phase);
+        }
+        assert_eq!(
+            phases,
+            [
+                PomodoroPhase::Work,
+                PomodoroPhase::ShortBreak,
+                PomodoroPhase::Work,
+                PomodoroPhase::LongBreak,
+                PomodoroPhase::Work,
+            ]
+        );
+        assert_eq!(run.completed, 2);
+    }
+
+    #[test]
+    fn a_break_counts_as_the_cycle_before_it() {
+        let mut run = PomodoroRun::new(config(4));
+        assert_eq!(run.cycle(), 1);
+        run.advance();
+        assert_eq!(run.cycle(), 1);
+        run.advance();
+        assert_eq!(run.cycle(), 2);
+    }
+
+    #[test]
+    fn phases_last_their_configured_minutes() {
+        let mut run = PomodoroRun::new(config(1));
+        assert_eq!(run.phase_duration(), Duration::from_secs(25 * 60));
+        run.advance();
+        assert_eq!(run.phase, PomodoroPhase::LongBreak);
+        assert_eq!(run.phase_duration(), Duration::from_secs(15 * 60));
+    }
+
+    #[test]
+    fn refuses_empty_phases_and_intervals() {
+        assert!(PomodoroConfig::default().validate().is_ok());
+        let no_work = PomodoroConfig {
+            work_minutes: 0,
+            ..PomodoroConfig::default()
+        };
+        assert!(no_work.validate().is_err());
+        assert!(config(0).validate().is_err());
+    }
+
+    #[test]
+    fn refuses_phases_longer_than_a_week() {
+        let week = PomodoroConfig {
+            work_minutes: 7 * 24 * 60,
+            ..PomodoroConfig::default()
+        };
+        assert!(week.validate().is_ok());
+        let longer = PomodoroConfig {
+            long_break_minutes: 7 * 24 * 60 + 1,
+            ..PomodoroConfig::default()
+        };
+        assert!(longer.validate().is_err());
+        let huge = PomodoroConfig {
+            work_minutes: u32::MAX,
+            ..PomodoroConfig::default()
+        };
+        assert!(huge.validate().is_err());
+    }
+}
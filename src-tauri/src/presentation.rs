@@ -0,0 +1,139 @@
+use std::path::PathBuf;
+
+use serde::Serialize;
+use tauri::{
+    AppHandle, Manager, Monitor, PhysicalPosition, WebviewUrl, WebviewWindow, WebviewWindowBuilder,
+};
+
+pub const PRESENTATION_LABEL: &str = "presentation";
+
+/// A monitor the dial can be presented on, as offered to the user.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MonitorInfo {
+    /// What `start` takes to pick this monitor.
+    pub index: usize,
+    pub name: String,
+    pub width: u32,
+    pub height: u32,
+    pub scale_factor: f64,
+    pub primary: bool,
+    /// The control window is on this one.
+    pub current: bool,
+}
+
+/// All connected monitors, left to right.
+pub fn monitors(control: &WebviewWindow) -> Result<Vec<MonitorInfo>, String> {
+    let primary = control.primary_monitor().ok().flatten();
+    let current = control.current_monitor().ok().flatten();
+    let is = |monitor: &Monitor, other: &Option<Monitor>| {
+        other
+            .as_ref()
+            .is_some_and(|other| other.position() == monitor.position())
+    };
+    Ok(sorted_monitors(control)?
+        .iter()
+        .enumerate()
+        .map(|(index, monitor)| MonitorInfo {
+            index,
+            name: monitor
+                .name()
+                .cloned()
+                .unwrap_or_else(|| format!("Display {}", index + 1)),
+            width: monitor.size().width,
+            height: monitor.size().height,
+            scale_factor: monitor.scale_factor(),
+            primary: is(monitor, &primary),
+            current: is(monitor, &current),
+        })
+        .collect())
+}
+
+/// Opens the dial fullscreen on monitor `index` in a window of its own, or
+/// moves it there if it is already open. If the control window is on that
+/// monitor it moves to another one, so the presenter keeps it at hand.
+pub fn start(app: &AppHandle, control: &WebviewWindow, index: usize) -> Result<(), String> {
+    let monitors = sorted_monitors(control)?;
+    let target = monitors
+        .get(index)
+        .ok_or_else(|| format!("There is no monitor {}", index + 1))?;
+
+    let scale = target.scale_factor();
+    let position = target.position().to_logical::<f64>(scale);
+    let size = target.size().to_logical::<f64>(scale);
+    // Destroying the window frees its label only later, so a running
+    // presentation is moved rather than replaced
+    let window = match app.get_webview_window(PRESENTATION_LABEL) {
+        Some(window) => {
+            window
+                .set_fullscreen(false)
+                .and_then(|_| window.set_position(position))
+                .and_then(|_| window.set_size(size))
+                .map_err(|e| format!("Failed to move presentation window: {}", e))?;
+            window
+        }
+        None => WebviewWindowBuilder::new(
+            app,
+            PRESENTATION_LABEL,
+            WebviewUrl::App(PathBuf::from("index.html?presentation")),
+        )
+        .title("Visual Countdown Timer")
+        .decorations(false)
+        .position(position.x, position.y)
+        .inner_size(size.width, size.height)
+        .build()
+        .map_err(|e| format!("Failed to open presentation window: {}", e))?,
+    };
+    window
+        .set_fullscreen(true)
+        .map_err(|e| format!("Failed to go fullscreen: {}", e))?;
+
+    let on_target = control
+        .current_monitor()
+        .ok()
+        .flatten()
+        .is_some_and(|monitor| monitor.position() == target.position());
+    if let (true, Some(other)) = (
+        on_target,
+        monitors
+            .iter()
+            .find(|monitor| monitor.position() != target.position()),
+    ) {
+        center_on(control, other)?;
+    }
+    Ok(())
+}
+
+/// Closes the presentation window; returns whether there was one.
+pub fn stop(app: &AppHandle) -> Result<bool, String> {
+    match app.get_webview_window(PRESENTATION_LABEL) {
+        Some(window) => window
+            .destroy()
+            .map(|_| true)
+            .map_err(|e| format!("Failed to close presentation window: {}", e)),
+        None => Ok(false),
+    }
+}
+
+// Left to right, then top to bottom, so the numbers stay put between calls.
+fn sorted_monitors(window: &WebviewWindow) -> Result<Vec<Monitor>, String> {
+    let mut monitors = window
+        .available_monitors()
+        .map_err(|e| format!("Failed to list monitors: {}", e))?;
+    monitors.sort_by_key(|monitor| (monitor.position().x, monitor.position().y));
+    Ok(monitors)
+}
+
+fn center_on(window: &WebviewWindow, monitor: &Monitor) -> Result<(), String> {
+    let area = monitor.work_area();
+    let size = window
+        .outer_size()
+        .map_err(|e| format!("Failed to read window size: {}", e))?;
+    let position = PhysicalPosition::new(
+        area.position.x + (area.size.width.saturating_sub(size.width) / 2) as i32,
+        area.position.y + (area.size.height.saturating_sub(size.height) / 2) as i32,
+    );
+    window
+        .set_position(position)
+        .map_err(|e| format!("Failed to move window: {}", e))
+}
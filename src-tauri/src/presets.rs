@@ -0,0 +1,158 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, LogicalSize, WebviewWindow};
+
+/// A window size preset, part of the settings file. Sizes are in logical
+/// pixels, so a preset looks the same on every display scale; the webview
+/// gets it as the `preset-<name>` class plus its font and padding values.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WindowPreset {
+    pub name: String,
+    pub width: f64,
+    pub height: f64,
+    /// Space around the dial.
+    pub padding: f64,
+    /// Minute labels on the dial.
+    pub font_size: f64,
+    /// The caption and buttons under the dial.
+    pub control_font_size: f64,
+    pub font_weight: u32,
+    pub letter_spacing: f64,
+}
+
+impl Default for WindowPreset {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            width: 500.0,
+            height: 500.0,
+            padding: 30.0,
+            font_size: 20.0,
+            control_font_size: 12.0,
+            font_weight: 400,
+            letter_spacing: 0.0,
+        }
+    }
+}
+
+/// The presets behind `Cmd/Ctrl + 1`–`5`, from smallest to largest.
+pub fn default_presets() -> Vec<WindowPreset> {
+    vec![
+        WindowPreset {
+            name: "xxs".to_string(),
+            width: 220.0,
+            height: 220.0,
+            padding: 5.0,
+            control_font_size: 9.0,
+            font_weight: 600,
+            letter_spacing: 1.0,
+            ..WindowPreset::default()
+        },
+        WindowPreset {
+            name: "xs".to_string(),
+            width: 300.0,
+            height: 300.0,
+            padding: 20.0,
+            control_font_size: 10.0,
+            font_weight: 500,
+            letter_spacing: 2.0,
+            ..WindowPreset::default()
+        },
+        WindowPreset {
+            name: "s".to_string(),
+            width: 400.0,
+            height: 400.0,
+            padding: 26.0,
+            control_font_size: 11.0,
+            ..WindowPreset::default()
+        },
+        WindowPreset {
+            name: "m".to_string(),
+            ..WindowPreset::default()
+        },
+        WindowPreset {
+            name: "l".to_string(),
+            width: 700.0,
+            height: 700.0,
+            padding: 40.0,
+            font_size: 28.0,
+            control_font_size: 14.0,
+            font_weight: 300,
+            ..WindowPreset::default()
+        },
+    ]
+}
+
+/// Checks what can be checked without the window: names usable as a class
+/// and sizes that make sense. The window limits are checked when a preset is
+/// applied.
+pub fn validate(presets: &[WindowPreset]) -> Result<(), String> {
+    if presets.is_empty() {
+        return Err("At least one window preset is needed".to_string());
+    }
+    for (index, preset) in presets.iter().enumerate() {
+        let name = &preset.name;
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(format!(
+                "Invalid preset name \"{}\", use letters, digits, - or _",
+                name
+            ));
+        }
+        if presets[..index].iter().any(|other| &other.name == name) {
+            return Err(format!("There is more than one preset named \"{}\"", name));
+        }
+        if preset.padding < 0.0 || preset.font_size <= 0.0 || preset.control_font_size <= 0.0 {
+            return Err(format!(
+                "Preset \"{}\" needs a positive font size and padding",
+                name
+            ));
+        }
+    }
+    Ok(())
+}
+
+pub fn find<'a>(presets: &'a [WindowPreset], name: &str) -> Result<&'a WindowPreset, String> {
+    presets
+        .iter()
+        .find(|preset| preset.name == name)
+        .ok_or_else(|| format!("Unknown preset \"{}\"", name))
+}
+
+/// Resizes `window` to `preset`, refusing sizes outside the window's
+/// `minWidth`/`maxWidth`/`minHeight`/`maxHeight` from `tauri.conf.json`.
+pub fn apply(app: &AppHandle, window: &WebviewWindow, preset: &WindowPreset) -> Result<(), String> {
+    let config = app
+        .config()
+        .app
+        .windows
+        .iter()
+        .find(|config| config.label == window.label());
+    if let Some(config) = config {
+        check_range("width", preset.width, config.min_width, config.max_width)?;
+        check_range(
+            "height",
+            preset.height,
+            config.min_height,
+            config.max_height,
+        )?;
+    }
+    window
+        .set_size(LogicalSize::new(preset.width, preset.height))
+        .map_err(|e| format!("Failed to resize window: {}", e))
+}
+
+fn check_range(what: &str, value: f64, min: Option<f64>, max: Option<f64>) -> Result<(), String> {
+    let min = min.unwrap_or(0.0);
+    let max = max.unwrap_or(f64::INFINITY);
+    if value < min || value > max {
+        return Err(format!(
+            "Preset {} {} is outside the window's {} to {}",
+            what, value, min, max
+        ));
+    }
+    Ok(())
+}
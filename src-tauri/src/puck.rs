@@ -0,0 +1,69 @@
+use tauri::menu::{Menu, MenuItem};
+use tauri::{PhysicalSize, WebviewWindow};
+
+const LEAVE_ITEM: &str = "puck-leave";
+const CLOSE_ITEM: &str = "puck-close";
+
+/// An entry picked from the puck's context menu.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PuckAction {
+    /// Back to the normal, decorated window.
+    Leave,
+    Close,
+}
+
+/// Hands entries picked from the context menu of `window` to `on_action`.
+pub fn init(
+    window: &WebviewWindow,
+    on_action: impl Fn(&WebviewWindow, PuckAction) + Send + Sync + 'static,
+) {
+    let handle = window.clone();
+    window.on_menu_event(move |_, event| {
+        let action = match event.id().as_ref() {
+            LEAVE_ITEM => PuckAction::Leave,
+            CLOSE_ITEM => PuckAction::Close,
+            _ => return,
+        };
+        on_action(&handle, action);
+    });
+}
+
+/// Turns `window` into a frameless, shadowless square so that, with the
+/// page drawn transparent around the dial, only the round dial shows; or
+/// gives it its frame back.
+pub fn apply(window: &WebviewWindow, active: bool) -> Result<(), String> {
+    let result = window
+        .set_decorations(!active)
+        .and_then(|_| window.set_shadow(!active));
+    result.map_err(|e| format!("Failed to switch puck mode: {}", e))?;
+    if !active {
+        return Ok(());
+    }
+
+    let size = window
+        .inner_size()
+        .map_err(|e| format!("Failed to read window size: {}", e))?;
+    let side = size.width.min(size.height);
+    window
+        .set_size(PhysicalSize::new(side, side))
+        .map_err(|e| format!("Failed to resize window: {}", e))
+}
+
+/// Shows the puck's context menu where the pointer is; there is no title
+/// bar to close it from.
+pub fn show_menu(window: &WebviewWindow) -> Result<(), String> {
+    let menu = build_menu(window).map_err(|e| format!("Failed to create menu: {}", e))?;
+    window
+        .popup_menu(&menu)
+        .map_err(|e| format!("Failed to show menu: {}", e))
+}
+
+fn build_menu(window: &WebviewWindow) -> tauri::Result<Menu<tauri::Wry>> {
+    Menu::with_items(
+        window,
+        &[
+            &MenuItem::with_id(window, LEAVE_ITEM, "Show Window Frame", true, None::<&str>)?,
+            &MenuItem::with_id(window, CLOSE_ITEM, "Close", true, None::<&str>)?,
+        ],
+    )
+}
@@ -0,0 +1,312 @@
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::duration;
+
+// Guards against typos like `repeat = 80000` producing a sequence that
+// could never be run through anyway.
+const MAX_STEPS: usize = 10_000;
+
+/// A sequence file as written by the user, in TOML or JSON:
+///
+/// ```toml
+/// name = "HIIT"
+///
+/// [[steps]]
+/// repeat = 8
+/// steps = [
+///     { name = "Work", duration = "40s" },
+///     { name = "Rest", duration = "20s" },
+/// ]
+///
+/// [[steps]]
+/// name = "Cooldown"
+/// duration = "2m"
+/// ```
+#[derive(Debug, Deserialize)]
+struct SequenceFile {
+    name: Option<String>,
+    steps: Vec<Entry>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Entry {
+    Repeat {
+        repeat: u32,
+        steps: Vec<Entry>,
+    },
+    Step {
+        name: String,
+        duration: DurationValue,
+    },
+}
+
+/// Durations are typed like in the window (`"40s"`, `"2m"`, `"1:30"`), or
+/// given as a plain number of seconds.
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum DurationValue {
+    Seconds(u64),
+    Text(String),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Step {
+    pub name: String,
+    pub seconds: u64,
+}
+
+/// A loaded sequence with all repeats expanded into a flat list of steps.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Sequence {
+    pub name: String,
+    pub steps: Vec<Step>,
+}
+
+/// Reads a `.toml` or `.json` sequence file.
+pub fn load(path: &Path) -> Result<Sequence, String> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .map(|ext| ext.to_ascii_lowercase());
+    let file: SequenceFile = match extension.as_deref() {
+        Some("toml") => toml::from_str(&contents).map_err(|e| e.to_string()),
+        Some("json") => serde_json::from_str(&contents).map_err(|e| e.to_string()),
+        _ => Err("expected a .toml or .json file".to_string()),
+    }
+    .map_err(|e| format!("Invalid sequence {}: {}", path.display(), e))?;
+
+    let fallback_name = path
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .unwrap_or("Sequence");
+    let name = file.name.unwrap_or_else(|| fallback_name.to_string());
+
+    let mut steps = Vec::new();
+    expand(&file.steps, &mut steps)?;
+    if steps.is_empty() {
+        return Err(format!("Sequence \"{}\" has no steps", name));
+    }
+    Ok(Sequence { name, steps })
+}
+
+fn expand(entries: &[Entry], steps: &mut Vec<Step>) -> Result<(), String> {
+    for entry in entries {
+        match entry {
+            Entry::Repeat {
+                repeat,
+                steps: inner,
+            } => {
+                for _ in 0..*repeat {
+                    let before = steps.len();
+                    expand(inner, steps)?;
+                    // Nothing to repeat; don't spin through a huge count.
+                    if steps.len() == before {
+                        break;
+                    }
+                }
+            }
+            Entry::Step { name, duration } => {
+                // Numbered as the expanded steps are run, repeats included
+                let seconds = match duration {
+                    DurationValue::Seconds(seconds) => {
+                        duration::check_seconds(*seconds).map(|_| *seconds)
+                    }
+                    DurationValue::Text(text) => duration::parse_duration(text),
+                }
+                .map_err(|e| format!("Step {} (\"{}\"): {}", steps.len() + 1, name, e))?;
+                steps.push(Step {
+                    name: name.clone(),
+                    seconds,
+                });
+            }
+        }
+
+        if steps.len() > MAX_STEPS {
+            return Err(format!("Sequence has more than {} steps", MAX_STEPS));
+        }
+    }
+    Ok(())
+}
+
+/// Progress through a running sequence.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SequenceRun {
+    pub sequence: Sequence,
+    pub index: usize,
+}
+
+/// The part of a run the webview shows next to the dial.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SequenceStatus {
+    pub name: String,
+    pub step_name: String,
+    /// 1-based position of the current step.
+    pub step: usize,
+    pub step_count: usize,
+}
+
+impl SequenceRun {
+    pub fn new(sequence: Sequence) -> Self {
+        Self { sequence, index: 0 }
+    }
+
+    /// A run read back from disk may not point at a real step, or hold
+    /// steps no timer could run.
+    pub fn is_valid(&self) -> bool {
+        self.index < self.sequence.steps.len()
+            && self
+                .sequence
+                .steps
+                .iter()
+                .all(|step| duration::check_seconds(step.seconds).is_ok())
+    }
+
+    pub fn current(&self) -> &Step {
+        &self.sequence.steps[self.index]
+    }
+
+    /// Moves to the next step; `false` once the last step is done.
+    pub fn advance(&mut self) -> bool {
+        if self.index + 1 < self.sequence.steps.len() {
+            self.index += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn status(&self) -> SequenceStatus {
+        SequenceStatus {
+            name: self.sequence.name.clone(),
+            step_name: self.current().name.clone(),
+            step: self.index + 1,
+            step_count: self.sequence.steps.len(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand_toml(text: &str) -> Result<Vec<Step>, String> {
+        let file: SequenceFile = toml::from_str(text).map_err(|e| e.to_string())?;
+        let mut steps = Vec::new();
+        expand(&file.steps, &mut steps)?;
+        Ok(steps)
+    }
+
+    fn names(steps: &[Step]) -> Vec<&str> {
+        steps.iter().map(|step| step.name.as_str()).collect()
+    }
+
+    #[test]
+    fn expands_repeats_in_order() {
+        let steps = expand_toml(
+            r#"
+            [[steps]]
+            repeat = 2
+            steps = [
+                { name = "Work", duration = "40s" },
+                { name = "Rest", duration = 20 },
+            ]
+
+            [[steps]]
+            name = "Cooldown"
+            duration = "2m"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(names(&steps), ["Work", "Rest", "Work", "Rest", "Cooldown"]);
+        assert_eq!(steps[1].seconds, 20);
+        assert_eq!(steps[4].seconds, 120);
+    }
+
+    #[test]
+    fn empty_repeat_adds_nothing() {
+        let steps = expand_toml(
+            r#"
+            [[steps]]
+            repeat = 4000000000
+            steps = []
+
+            [[steps]]
+            name = "Only"
+            duration = "1m"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(names(&steps), ["Only"]);
+    }
+
+    #[test]
+    fn names_the_step_with_a_bad_duration() {
+        let error = expand_toml(
+            r#"
+            [[steps]]
+            name = "Warm-up"
+            duration = "1m"
+
+            [[steps]]
+            name = "Forever"
+            duration = 9999999999999
+            "#,
+        )
+        .unwrap_err();
+        assert!(error.starts_with("Step 2 (\"Forever\")"), "{}", error);
+
+        let error = expand_toml(
+            r#"
+            [[steps]]
+            name = "Nothing"
+            duration = 0
+            "#,
+        )
+        .unwrap_err();
+        assert!(error.starts_with("Step 1 (\"Nothing\")"), "{}", error);
+        assert!(expand_toml("steps = [{ name = \"Typo\", duration = \"5x\" }]").is_err());
+    }
+
+    #[test]
+    fn refuses_too_many_steps() {
+        let error = expand_toml(
+            r#"
+            [[steps]]
+            repeat = 10001
+            steps = [{ name = "Tick", duration = 1 }]
+            "#,
+        )
+        .unwrap_err();
+        assert!(error.contains("more than"), "{}", error);
+    }
+
+    #[test]
+    fn saved_runs_must_point_at_a_runnable_step() {
+        let sequence = Sequence {
+            name: "Saved".to_string(),
+            steps: vec![Step {
+                name: "One".to_string(),
+                seconds: 60,
+            }],
+        };
+        let mut run = SequenceRun::new(sequence);
+        assert!(run.is_valid());
+        assert!(!run.advance());
+        run.index = 1;
+        assert!(!run.is_valid());
+        run.index = 0;
+        run.sequence.steps[0].seconds = u64::MAX;
+        assert!(!run.is_valid());
+    }
+}
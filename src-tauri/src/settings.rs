@@ -0,0 +1,125 @@
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::audio::SoundConfig;
+use crate::dial;
+use crate::overlay::OverlayConfig;
+use crate::persistence;
+use crate::pin::PinConfig;
+use crate::pomodoro::PomodoroConfig;
+use crate::presets::{self, WindowPreset};
+use crate::shortcuts::ShortcutConfig;
+use crate::snap;
+use crate::warning::{self, Warning};
+
+const SETTINGS_FILE: &str = "settings.json";
+
+/// User preferences, stored as `settings.json` in the app data directory.
+/// Missing keys fall back to their defaults so older files keep loading.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    pub dial_span_minutes: u32,
+    pub pomodoro: PomodoroConfig,
+    /// Plain countdowns keep counting past zero until stopped.
+    pub overtime: bool,
+    pub sound: SoundConfig,
+    pub warnings: Vec<Warning>,
+    pub shortcuts: ShortcutConfig,
+    pub pin: PinConfig,
+    pub overlay: OverlayConfig,
+    pub presets: Vec<WindowPreset>,
+    /// A frameless window showing only the round dial.
+    pub puck: bool,
+    /// Space between a snapped window and the screen edge, in logical pixels.
+    pub snap_margin: f64,
+    /// The length of the last plain countdown started, for the "start last
+    /// duration" shortcut.
+    pub last_duration_seconds: Option<u64>,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            dial_span_minutes: dial::DEFAULT_DIAL_SPAN,
+            pomodoro: PomodoroConfig::default(),
+            overtime: false,
+            sound: SoundConfig::default(),
+            warnings: warning::default_warnings(),
+            shortcuts: ShortcutConfig::default(),
+            pin: PinConfig::default(),
+            overlay: OverlayConfig::default(),
+            presets: presets::default_presets(),
+            puck: false,
+            snap_margin: snap::DEFAULT_MARGIN,
+            last_duration_seconds: None,
+        }
+    }
+}
+
+impl Settings {
+    // Values edited by hand may be out of range; fix them up rather than
+    // refusing to start.
+    fn sanitized(mut self) -> Self {
+        if dial::check_span(self.dial_span_minutes).is_err() {
+            self.dial_span_minutes = dial::DEFAULT_DIAL_SPAN;
+        }
+        if self.pomodoro.validate().is_err() {
+            self.pomodoro = PomodoroConfig::default();
+        }
+        if self.sound.validate().is_err() {
+            self.sound = SoundConfig::default();
+        }
+        if warning::validate(&self.warnings).is_err() {
+            self.warnings = warning::default_warnings();
+        }
+        if self.shortcuts.validate().is_err() {
+            self.shortcuts = ShortcutConfig::default();
+        }
+        if self.overlay.validate().is_err() {
+            self.overlay = OverlayConfig::default();
+        }
+        if presets::validate(&self.presets).is_err() {
+            self.presets = presets::default_presets();
+        }
+        if snap::check_margin(self.snap_margin).is_err() {
+            self.snap_margin = snap::DEFAULT_MARGIN;
+        }
+        self
+    }
+}
+
+/// Managed as Tauri state; every change is written straight to disk.
+pub struct SettingsStore {
+    settings: Mutex<Settings>,
+}
+
+impl SettingsStore {
+    pub fn load(app: &AppHandle) -> Self {
+        let settings: Settings = persistence::read_json(app, SETTINGS_FILE).unwrap_or_default();
+        Self {
+            settings: Mutex::new(settings.sanitized()),
+        }
+    }
+
+    pub fn get(&self) -> Settings {
+        self.settings.lock().unwrap().clone()
+    }
+
+    /// Applies `change` and saves the result; if saving fails the settings
+    /// stay as they were, so memory and disk agree.
+    pub fn update(
+        &self,
+        app: &AppHandle,
+        change: impl FnOnce(&mut Settings),
+    ) -> Result<Settings, String> {
+        let mut settings = self.settings.lock().unwrap();
+        let mut changed = settings.clone();
+        change(&mut changed);
+        persistence::write_json(app, SETTINGS_FILE, &changed)?;
+        *settings = changed.clone();
+        Ok(changed)
+    }
+}
@@ -0,0 +1,174 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut, ShortcutState};
+
+/// What a global shortcut does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ShortcutAction {
+    /// Starts a countdown of the last duration that was started.
+    StartLast,
+    PauseResume,
+    Stop,
+    /// Shows the window, or hides it when it is already visible.
+    ToggleWindow,
+    ToggleAlwaysOnTop,
+    ToggleAllWorkspaces,
+    /// Enters overlay mode, or leaves it: the window can't be clicked then.
+    ToggleOverlay,
+}
+
+/// System-wide hotkeys, part of the settings file. Each one is an
+/// accelerator such as `CmdOrCtrl+Shift+Alt+P`; `null` turns it off. The
+/// defaults add Shift so they stay clear of AltGr characters (Ctrl+Alt on
+/// Windows) and desktop bindings such as GNOME's Ctrl+Alt+T.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ShortcutConfig {
+    pub start_last: Option<String>,
+    pub pause_resume: Option<String>,
+    pub stop: Option<String>,
+    pub toggle_window: Option<String>,
+    pub always_on_top: Option<String>,
+    pub all_workspaces: Option<String>,
+    pub overlay: Option<String>,
+}
+
+impl Default for ShortcutConfig {
+    fn default() -> Self {
+        Self {
+            start_last: Some("CmdOrCtrl+Shift+Alt+S".to_string()),
+            pause_resume: Some("CmdOrCtrl+Shift+Alt+P".to_string()),
+            stop: Some("CmdOrCtrl+Shift+Alt+X".to_string()),
+            toggle_window: Some("CmdOrCtrl+Shift+Alt+W".to_string()),
+            always_on_top: Some("CmdOrCtrl+Shift+Alt+T".to_string()),
+            all_workspaces: Some("CmdOrCtrl+Shift+Alt+A".to_string()),
+            overlay: Some("CmdOrCtrl+Shift+Alt+O".to_string()),
+        }
+    }
+}
+
+impl ShortcutConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        let mut seen: Vec<Shortcut> = Vec::new();
+        for (accelerator, _) in self.bindings() {
+            let shortcut = parse(accelerator)?;
+            if seen.contains(&shortcut) {
+                return Err(format!("Shortcut {} is used more than once", accelerator));
+            }
+            seen.push(shortcut);
+        }
+        Ok(())
+    }
+
+    fn bindings(&self) -> impl Iterator<Item = (&str, ShortcutAction)> {
+        [
+            (&self.start_last, ShortcutAction::StartLast),
+            (&self.pause_resume, ShortcutAction::PauseResume),
+            (&self.stop, ShortcutAction::Stop),
+            (&self.toggle_window, ShortcutAction::ToggleWindow),
+            (&self.always_on_top, ShortcutAction::ToggleAlwaysOnTop),
+            (&self.all_workspaces, ShortcutAction::ToggleAllWorkspaces),
+            (&self.overlay, ShortcutAction::ToggleOverlay),
+        ]
+        .into_iter()
+        .filter_map(|(accelerator, action)| Some((accelerator.as_deref()?, action)))
+    }
+}
+
+fn parse(accelerator: &str) -> Result<Shortcut, String> {
+    Shortcut::from_str(accelerator)
+        .map_err(|e| format!("Invalid shortcut \"{}\": {}", accelerator, e))
+}
+
+/// Replaces the registered hotkeys with those in `config`, handing presses
+/// to `on_action`. A hotkey another app already holds doesn't keep the rest
+/// from working; each failure is logged as it happens and all of them are
+/// reported together.
+pub fn register(
+    app: &AppHandle,
+    config: &ShortcutConfig,
+    on_action: impl Fn(&AppHandle, ShortcutAction) + Copy + Send + Sync + 'static,
+) -> Result<(), String> {
+    let global_shortcut = app.global_shortcut();
+    global_shortcut
+        .unregister_all()
+        .map_err(|e| format!("Failed to clear shortcuts: {}", e))?;
+
+    let mut errors = Vec::new();
+    for (accelerator, action) in config.bindings() {
+        let result = parse(accelerator).and_then(|shortcut| {
+            global_shortcut
+                .on_shortcut(shortcut, move |app, _, event| {
+                    if event.state == ShortcutState::Pressed {
+                        on_action(app, action);
+                    }
+                })
+                .map_err(|e| format!("Failed to register shortcut {}: {}", accelerator, e))
+        });
+        if let Err(e) = result {
+            eprintln!("{}", e);
+            errors.push(e);
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("; "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_defaults_are_valid() {
+        assert!(ShortcutConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn refuses_a_shortcut_used_twice() {
+        let config = ShortcutConfig {
+            stop: Some("CmdOrCtrl+Shift+Alt+P".to_string()),
+            ..ShortcutConfig::default()
+        };
+        assert!(config.validate().is_err());
+
+        // The same keys written another way are still the same shortcut
+        let config = ShortcutConfig {
+            stop: Some("Alt+Shift+CmdOrCtrl+P".to_string()),
+            ..ShortcutConfig::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn refuses_a_shortcut_that_cant_be_read() {
+        let config = ShortcutConfig {
+            overlay: Some("Ctrl+Shift+Nope".to_string()),
+            ..ShortcutConfig::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn turned_off_shortcuts_are_skipped() {
+        let config = ShortcutConfig {
+            start_last: None,
+            pause_resume: None,
+            stop: None,
+            toggle_window: None,
+            always_on_top: None,
+            all_workspaces: None,
+            overlay: Some("CmdOrCtrl+Shift+Alt+O".to_string()),
+        };
+        assert!(config.validate().is_ok());
+        let actions = config
+            .bindings()
+            .map(|(_, action)| action)
+            .collect::<Vec<_>>();
+        assert_eq!(actions, [ShortcutAction::ToggleOverlay]);
+    }
+}
@@ -0,0 +1,260 @@
+use serde::{Deserialize, Serialize};
+use tauri::{Monitor, PhysicalPosition, PhysicalRect, WebviewWindow};
+
+pub const DEFAULT_MARGIN: f64 = 16.0;
+const MAX_MARGIN: f64 = 200.0;
+
+/// Where on the monitor to put the window: a corner, or the middle of an
+/// edge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum SnapPosition {
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+pub fn check_margin(margin: f64) -> Result<(), String> {
+    if !(0.0..=MAX_MARGIN).contains(&margin) {
+        return Err(format!("Snap margin must be between 0 and {}", MAX_MARGIN));
+    }
+    Ok(())
+}
+
+/// Moves `window` to `position` on the monitor it is on, `margin` logical
+/// pixels inside the work area so it stays clear of panels and docks.
+pub fn snap(window: &WebviewWindow, position: SnapPosition, margin: f64) -> Result<(), String> {
+    let monitor = current_monitor(window)?;
+    let area = Rect::of(monitor.work_area()).inset(margin * monitor.scale_factor());
+    let size = window
+        .outer_size()
+        .map_err(|e| format!("Failed to read window size: {}", e))?;
+    move_to(window, snapped(area, (size.width, size.height), position))
+}
+
+/// Moves `window` to the next monitor, left to right, keeping its place
+/// relative to the work area: a window in the top-right corner lands in
+/// the top-right corner of the next monitor.
+pub fn next_monitor(window: &WebviewWindow, margin: f64) -> Result<(), String> {
+    let mut monitors = window
+        .available_monitors()
+        .map_err(|e| format!("Failed to list monitors: {}", e))?;
+    if monitors.len() < 2 {
+        return Err("There is no other monitor".to_string());
+    }
+    monitors.sort_by_key(|monitor| (monitor.position().x, monitor.position().y));
+    let current = current_monitor(window)?;
+    let index = monitors
+        .iter()
+        .position(|monitor| monitor.position() == current.position())
+        .unwrap_or(0);
+    let next = &monitors[(index + 1) % monitors.len()];
+
+    let position = window
+        .outer_position()
+        .map_err(|e| format!("Failed to read window position: {}", e))?;
+    let size = window
+        .outer_size()
+        .map_err(|e| format!("Failed to read window size: {}", e))?;
+    move_to(
+        window,
+        carried_over(
+            Rect {
+                x: position.x,
+                y: position.y,
+                width: size.width,
+                height: size.height,
+            },
+            Rect::of(current.work_area()).inset(margin * current.scale_factor()),
+            Rect::of(next.work_area()).inset(margin * next.scale_factor()),
+            next.scale_factor() / current.scale_factor(),
+        ),
+    )
+}
+
+/// A work area or window, in physical pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Rect {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+}
+
+impl Rect {
+    fn of(area: &PhysicalRect<i32, u32>) -> Self {
+        Self {
+            x: area.position.x,
+            y: area.position.y,
+            width: area.size.width,
+            height: area.size.height,
+        }
+    }
+
+    // Shrunk by `margin` physical pixels on every side.
+    fn inset(self, margin: f64) -> Self {
+        let margin = margin.round() as u32;
+        Self {
+            x: self.x + margin as i32,
+            y: self.y + margin as i32,
+            width: self.width.saturating_sub(2 * margin),
+            height: self.height.saturating_sub(2 * margin),
+        }
+    }
+}
+
+// Where a window of `size` goes to sit at `position` in `area`. One too big
+// for the area keeps to its top left.
+fn snapped(area: Rect, size: (u32, u32), position: SnapPosition) -> (i32, i32) {
+    let free_width = area.width.saturating_sub(size.0) as i32;
+    let free_height = area.height.saturating_sub(size.1) as i32;
+    let (across, down) = match position {
+        SnapPosition::TopLeft => (0, 0),
+        SnapPosition::Top => (1, 0),
+        SnapPosition::TopRight => (2, 0),
+        SnapPosition::Left => (0, 1),
+        SnapPosition::Right => (2, 1),
+        SnapPosition::BottomLeft => (0, 2),
+        SnapPosition::Bottom => (1, 2),
+        SnapPosition::BottomRight => (2, 2),
+    };
+    (
+        area.x + free_width * across / 2,
+        area.y + free_height * down / 2,
+    )
+}
+
+// Where `window` goes in the `to` area to keep its place from the `from`
+// one. `scale` is how much bigger it gets in pixels on the other monitor.
+fn carried_over(window: Rect, from: Rect, to: Rect, scale: f64) -> (i32, i32) {
+    let width = (window.width as f64 * scale) as i32;
+    let height = (window.height as f64 * scale) as i32;
+    let share = |offset: i32, free: i32| {
+        if free > 0 {
+            (offset as f64 / free as f64).clamp(0.0, 1.0)
+        } else {
+            0.0
+        }
+    };
+    let across = share(window.x - from.x, from.width as i32 - window.width as i32);
+    let down = share(window.y - from.y, from.height as i32 - window.height as i32);
+    (
+        to.x + ((to.width as i32 - width).max(0) as f64 * across) as i32,
+        to.y + ((to.height as i32 - height).max(0) as f64 * down) as i32,
+    )
+}
+
+fn current_monitor(window: &WebviewWindow) -> Result<Monitor, String> {
+    let monitor = match window.current_monitor() {
+        Ok(Some(monitor)) => Some(monitor),
+        _ => window.primary_monitor().ok().flatten(),
+    };
+    monitor.ok_or_else(|| "Failed to find the window's monitor".to_string())
+}
+
+fn move_to(window: &WebviewWindow, (x, y): (i32, i32)) -> Result<(), String> {
+    window
+        .set_position(PhysicalPosition::new(x, y))
+        .map_err(|e| format!("Failed to move window: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A 1920x1080 monitor with a 40 px panel at the top
+    const AREA: Rect = Rect {
+        x: 0,
+        y: 40,
+        width: 1920,
+        height: 1040,
+    };
+
+    #[test]
+    fn corners_and_edges_keep_the_margin() {
+        let area = AREA.inset(16.0);
+        let size = (300, 200);
+        assert_eq!(snapped(area, size, SnapPosition::TopLeft), (16, 56));
+        assert_eq!(snapped(area, size, SnapPosition::Top), (810, 56));
+        assert_eq!(snapped(area, size, SnapPosition::TopRight), (1604, 56));
+        assert_eq!(snapped(area, size, SnapPosition::Left), (16, 460));
+        assert_eq!(snapped(area, size, SnapPosition::Right), (1604, 460));
+        assert_eq!(snapped(area, size, SnapPosition::BottomLeft), (16, 864));
+        assert_eq!(snapped(area, size, SnapPosition::Bottom), (810, 864));
+        assert_eq!(snapped(area, size, SnapPosition::BottomRight), (1604, 864));
+    }
+
+    #[test]
+    fn a_window_bigger_than_the_area_keeps_to_its_top_left() {
+        let area = AREA.inset(16.0);
+        assert_eq!(
+            snapped(area, (4000, 3000), SnapPosition::BottomRight),
+            (16, 56)
+        );
+    }
+
+    #[test]
+    fn the_margin_comes_off_every_side() {
+        let area = AREA.inset(32.0);
+        assert_eq!(
+            area,
+            Rect {
+                x: 32,
+                y: 72,
+                width: 1856,
+                height: 976,
+            }
+        );
+        assert_eq!(AREA.inset(0.0), AREA);
+    }
+
+    #[test]
+    fn the_next_monitor_gets_the_same_corner() {
+        let from = AREA.inset(16.0);
+        // A 2560x1440 monitor to the right, without a panel
+        let to = Rect {
+            x: 1920,
+            y: 0,
+            width: 2560,
+            height: 1440,
+        }
+        .inset(16.0);
+        let window = |(x, y)| Rect {
+            x,
+            y,
+            width: 300,
+            height: 200,
+        };
+
+        let top_right = window(snapped(from, (300, 200), SnapPosition::TopRight));
+        assert_eq!(carried_over(top_right, from, to, 1.0), (4164, 16));
+        let bottom_left = window(snapped(from, (300, 200), SnapPosition::BottomLeft));
+        assert_eq!(carried_over(bottom_left, from, to, 1.0), (1936, 1224));
+        let middle = window(snapped(from, (300, 200), SnapPosition::Left));
+        assert_eq!(carried_over(middle, from, to, 1.0).1, 16 + (1408 - 200) / 2);
+    }
+
+    #[test]
+    fn the_next_monitor_may_draw_the_window_bigger() {
+        let from = AREA;
+        let to = Rect {
+            x: -1920,
+            y: 0,
+            width: 3840,
+            height: 2160,
+        };
+        // Bottom right on a 1x monitor, twice the pixels on a 2x one
+        let window = Rect {
+            x: 1620,
+            y: 880,
+            width: 300,
+            height: 200,
+        };
+        assert_eq!(carried_over(window, from, to, 2.0), (1320, 1760));
+    }
+}
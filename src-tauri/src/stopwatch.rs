@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+
+/// Laps of a running stopwatch, stored as total elapsed time at each lap.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StopwatchRun {
+    pub lap_totals_ms: Vec<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Lap {
+    pub number: usize,
+    /// Time since the previous lap (or the start, for the first one).
+    pub split_ms: u64,
+    pub total_ms: u64,
+}
+
+/// The part of a stopwatch the webview shows in the lap list.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StopwatchStatus {
+    pub laps: Vec<Lap>,
+}
+
+impl StopwatchRun {
+    pub fn record_lap(&mut self, elapsed_ms: u64) {
+        self.lap_totals_ms.push(elapsed_ms);
+    }
+
+    pub fn status(&self) -> StopwatchStatus {
+        let mut previous = 0;
+        let laps = self
+            .lap_totals_ms
+            .iter()
+            .enumerate()
+            .map(|(index, &total_ms)| {
+                let split_ms = total_ms.saturating_sub(previous);
+                previous = total_ms;
+                Lap {
+                    number: index + 1,
+                    split_ms,
+                    total_ms,
+                }
+            })
+            .collect();
+        StopwatchStatus { laps }
+    }
+}
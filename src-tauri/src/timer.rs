@@ -0,0 +1,1022 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::duration::MAX_SECONDS;
+use crate::pomodoro::{PomodoroConfig, PomodoroRun, PomodoroStatus};
+use crate::sequence::{Sequence, SequenceRun, SequenceStatus};
+use crate::stopwatch::{StopwatchRun, StopwatchStatus};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimerStatus {
+    Idle,
+    Running,
+    Paused,
+    /// Past zero and still counting how far over it is.
+    Overtime,
+    Finished,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimerMode {
+    Countdown,
+    Pomodoro,
+    Sequence,
+    Stopwatch,
+}
+
+/// Snapshot of the countdown, sent to the webview on every change.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimerState {
+    pub status: TimerStatus,
+    pub mode: TimerMode,
+    /// Duration of the current Pomodoro phase or sequence step; the whole
+    /// timer for a plain countdown.
+    pub duration_seconds: u64,
+    /// Whole seconds left, rounded up so "0" only shows once finished.
+    pub remaining_seconds: u64,
+    pub remaining_ms: u64,
+    /// Time counted so far: up from zero for the stopwatch, the used part
+    /// of the duration for everything else.
+    pub elapsed_ms: u64,
+    /// How far past zero the countdown is in overtime, or was when stopped.
+    pub overrun_ms: u64,
+    pub label: Option<String>,
+    /// Wall-clock end of a countdown started "until" a time, as displayed.
+    pub target: Option<String>,
+    /// The timer ran out while the app was closed.
+    pub finished_while_away: bool,
+    pub pomodoro: Option<PomodoroStatus>,
+    pub sequence: Option<SequenceStatus>,
+    pub stopwatch: Option<StopwatchStatus>,
+}
+
+/// What a tick did besides updating the remaining time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TickEvent {
+    Tick,
+    /// A Pomodoro phase or sequence step ran out and the next one started.
+    PhaseChanged,
+    /// The countdown reached zero and went on counting into overtime.
+    Overtime,
+    /// A warning set up for this many seconds before the end is due.
+    Warning(u64),
+    Finished,
+}
+
+#[derive(Debug, Clone)]
+enum Mode {
+    Countdown,
+    Pomodoro(PomodoroRun),
+    Sequence(SequenceRun),
+    Stopwatch(StopwatchRun),
+}
+
+/// What survives a restart. `Instant` is meaningless across processes, so
+/// a running timer is stored by its wall-clock deadline instead.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedTimer {
+    pub status: TimerStatus,
+    pub duration_seconds: u64,
+    pub deadline_unix_ms: Option<u64>,
+    pub paused_remaining_ms: Option<u64>,
+    pub label: Option<String>,
+    #[serde(default)]
+    pub target: Option<String>,
+    #[serde(default)]
+    pub pomodoro: Option<PomodoroRun>,
+    #[serde(default)]
+    pub sequence: Option<SequenceRun>,
+    #[serde(default)]
+    pub stopwatch: Option<StopwatchRun>,
+    /// Stopwatch time counted before `started_unix_ms` (or before pausing).
+    #[serde(default)]
+    pub elapsed_base_ms: u64,
+    #[serde(default)]
+    pub started_unix_ms: Option<u64>,
+}
+
+impl SavedTimer {
+    // A hand-edited or corrupt file may hold times no timer could reach, or
+    // Pomodoro phases that couldn't be set; those are refused before the
+    // engine is touched.
+    fn check(&self) -> Result<(), String> {
+        let longest_ms = MAX_SECONDS * 1000;
+        let left_ms = self
+            .deadline_unix_ms
+            .map(|deadline| deadline.saturating_sub(unix_ms(SystemTime::now())));
+        if self.duration_seconds > MAX_SECONDS
+            || self.paused_remaining_ms.is_some_and(|ms| ms > longest_ms)
+            || left_ms.is_some_and(|ms| ms > longest_ms)
+        {
+            return Err("Saved timer runs longer than any timer can".to_string());
+        }
+        if let Some(run) = &self.pomodoro {
+            run.config.validate()?;
+        }
+        Ok(())
+    }
+}
+
+struct Inner {
+    status: TimerStatus,
+    mode: Mode,
+    duration: Duration,
+    // Monotonic end of the running countdown. Remaining time is always
+    // derived from it, so late or skipped ticks never lose seconds.
+    deadline: Option<Instant>,
+    // Time left when the countdown was paused; the deadline is dropped
+    // while paused and recomputed from this on resume.
+    paused_remaining: Duration,
+    // The stopwatch counts up instead: time banked before the current run,
+    // plus however long it has been running since `origin`.
+    elapsed_base: Duration,
+    origin: Option<Instant>,
+    // Whether a plain countdown goes into overtime at zero (a setting), and
+    // the overrun it had when stopped there.
+    overtime: bool,
+    overrun: Duration,
+    // Time left at which to warn, the same for every mode.
+    warnings: Vec<Duration>,
+    label: Option<String>,
+    target: Option<String>,
+    finished_while_away: bool,
+    // Bumped on every start/stop so a stale countdown thread knows to exit.
+    generation: u64,
+}
+
+impl Inner {
+    fn remaining(&self, now: Instant) -> Duration {
+        match (self.status, self.deadline) {
+            (TimerStatus::Running, Some(deadline)) => deadline.saturating_duration_since(now),
+            (TimerStatus::Paused, _) => self.paused_remaining,
+            _ => Duration::ZERO,
+        }
+    }
+
+    fn elapsed(&self, now: Instant) -> Duration {
+        match self.mode {
+            Mode::Stopwatch(_) => {
+                let running = self
+                    .origin
+                    .map(|origin| now.saturating_duration_since(origin));
+                self.elapsed_base + running.unwrap_or_default()
+            }
+            _ => self.duration.saturating_sub(self.remaining(now)),
+        }
+    }
+
+    // Ticks land just after each whole second, so a warning is due on the
+    // tick that ends the second it falls in. A timer (or phase) that isn't
+    // longer than the warning doesn't get it.
+    fn warning_due(&self, now: Instant) -> Option<u64> {
+        if self.status != TimerStatus::Running || matches!(self.mode, Mode::Stopwatch(_)) {
+            return None;
+        }
+        let remaining = self.remaining(now);
+        self.warnings
+            .iter()
+            .find(|&&left| {
+                left < self.duration
+                    && remaining <= left
+                    && left < remaining + Duration::from_secs(1)
+            })
+            .map(Duration::as_secs)
+    }
+
+    // In overtime the deadline stays put and lies in the past.
+    fn overrun(&self, now: Instant) -> Duration {
+        match (self.status, self.deadline) {
+            (TimerStatus::Overtime, Some(deadline)) => now.saturating_duration_since(deadline),
+            _ => self.overrun,
+        }
+    }
+
+    fn snapshot(&self, now: Instant) -> TimerState {
+        let remaining_ms = self.remaining(now).as_millis() as u64;
+        let (mode, pomodoro, sequence, stopwatch) = match &self.mode {
+            Mode::Countdown => (TimerMode::Countdown, None, None, None),
+            Mode::Pomodoro(run) => (TimerMode::Pomodoro, Some(run.status()), None, None),
+            Mode::Sequence(run) => (TimerMode::Sequence, None, Some(run.status()), None),
+            Mode::Stopwatch(run) => (TimerMode::Stopwatch, None, None, Some(run.status())),
+        };
+        TimerState {
+            status: self.status,
+            mode,
+            duration_seconds: self.duration.as_secs(),
+            remaining_seconds: remaining_ms.div_ceil(1000),
+            remaining_ms,
+            elapsed_ms: self.elapsed(now).as_millis() as u64,
+            overrun_ms: self.overrun(now).as_millis() as u64,
+            label: self.label.clone(),
+            target: self.target.clone(),
+            finished_while_away: self.finished_while_away,
+            pomodoro,
+            sequence,
+            stopwatch,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.status = TimerStatus::Idle;
+        self.mode = Mode::Countdown;
+        self.duration = Duration::ZERO;
+        self.deadline = None;
+        self.elapsed_base = Duration::ZERO;
+        self.origin = None;
+        self.overrun = Duration::ZERO;
+        self.label = None;
+        self.target = None;
+        self.finished_while_away = false;
+    }
+}
+
+/// Single source of truth for the countdown. Managed as Tauri state.
+pub struct TimerEngine {
+    inner: Mutex<Inner>,
+}
+
+impl Default for TimerEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TimerEngine {
+    pub fn new() -> Self {
+        Self {
+            inner: Mutex::new(Inner {
+                status: TimerStatus::Idle,
+                mode: Mode::Countdown,
+                duration: Duration::ZERO,
+                deadline: None,
+                paused_remaining: Duration::ZERO,
+                elapsed_base: Duration::ZERO,
+                origin: None,
+                overtime: false,
+                overrun: Duration::ZERO,
+                warnings: Vec::new(),
+                label: None,
+                target: None,
+                finished_while_away: false,
+                generation: 0,
+            }),
+        }
+    }
+
+    pub fn state(&self) -> TimerState {
+        self.inner.lock().unwrap().snapshot(Instant::now())
+    }
+
+    /// Starts a countdown and returns its generation, which the caller
+    /// hands to the thread driving `tick`.
+    pub fn start(&self, seconds: u64, label: Option<String>) -> Result<(TimerState, u64), String> {
+        self.start_countdown(Duration::from_secs(seconds), label, None)
+    }
+
+    /// Starts a countdown ending at a wall-clock time; `target` is that
+    /// time as the webview should show it.
+    pub fn start_until(
+        &self,
+        remaining: Duration,
+        label: Option<String>,
+        target: String,
+    ) -> Result<(TimerState, u64), String> {
+        self.start_countdown(remaining, label, Some(target))
+    }
+
+    fn start_countdown(
+        &self,
+        duration: Duration,
+        label: Option<String>,
+        target: Option<String>,
+    ) -> Result<(TimerState, u64), String> {
+        let now = Instant::now();
+        let deadline = end_of(now, duration)?;
+        let mut inner = self.inner.lock().unwrap();
+        inner.generation += 1;
+        inner.reset();
+        if !duration.is_zero() {
+            inner.status = TimerStatus::Running;
+            inner.duration = duration;
+            inner.deadline = Some(deadline);
+            inner.label = label;
+            inner.target = target;
+        }
+        Ok((inner.snapshot(now), inner.generation))
+    }
+
+    /// Starts a Pomodoro session with its first work phase. Phases then
+    /// follow each other automatically until the session is stopped.
+    pub fn start_pomodoro(&self, config: PomodoroConfig) -> Result<(TimerState, u64), String> {
+        let now = Instant::now();
+        let run = PomodoroRun::new(config);
+        let deadline = end_of(now, run.phase_duration())?;
+        let mut inner = self.inner.lock().unwrap();
+        inner.generation += 1;
+        inner.reset();
+
+        inner.status = TimerStatus::Running;
+        inner.duration = run.phase_duration();
+        inner.deadline = Some(deadline);
+        inner.mode = Mode::Pomodoro(run);
+        Ok((inner.snapshot(now), inner.generation))
+    }
+
+    /// Runs through the steps of a loaded sequence, one after another.
+    pub fn start_sequence(&self, sequence: Sequence) -> Result<(TimerState, u64), String> {
+        let now = Instant::now();
+        let run = SequenceRun::new(sequence);
+        let duration = Duration::from_secs(run.current().seconds);
+        let deadline = end_of(now, duration)?;
+        let mut inner = self.inner.lock().unwrap();
+        inner.generation += 1;
+        inner.reset();
+
+        inner.status = TimerStatus::Running;
+        inner.duration = duration;
+        inner.deadline = Some(deadline);
+        inner.label = Some(run.sequence.name.clone());
+        inner.mode = Mode::Sequence(run);
+        Ok((inner.snapshot(now), inner.generation))
+    }
+
+    /// Starts counting up from zero.
+    pub fn start_stopwatch(&self) -> (TimerState, u64) {
+        let now = Instant::now();
+        let mut inner = self.inner.lock().unwrap();
+        inner.generation += 1;
+        inner.reset();
+        inner.status = TimerStatus::Running;
+        inner.origin = Some(now);
+        inner.mode = Mode::Stopwatch(StopwatchRun::default());
+        (inner.snapshot(now), inner.generation)
+    }
+
+    /// Records a lap on a running stopwatch. Anything else is left untouched.
+    pub fn record_lap(&self) -> TimerState {
+        let now = Instant::now();
+        let mut guard = self.inner.lock().unwrap();
+        let inner = &mut *guard;
+        let elapsed_ms = inner.elapsed(now).as_millis() as u64;
+        if let (TimerStatus::Running, Mode::Stopwatch(run)) = (inner.status, &mut inner.mode) {
+            run.record_lap(elapsed_ms);
+        }
+        inner.snapshot(now)
+    }
+
+    /// Adds time to a running or paused timer. One that has finished or is
+    /// in overtime starts again as a countdown of `extra`, keeping its label.
+    /// Returns the new generation when that needs a countdown thread.
+    pub fn extend(&self, extra: Duration) -> Result<(TimerState, Option<u64>), String> {
+        let now = Instant::now();
+        let mut inner = self.inner.lock().unwrap();
+        match inner.status {
+            TimerStatus::Running => {
+                if let Some(deadline) = inner.deadline {
+                    let deadline = end_of(deadline, extra)?;
+                    let duration = longer(inner.duration, extra)?;
+                    inner.deadline = Some(deadline);
+                    inner.duration = duration;
+                }
+                Ok((inner.snapshot(now), None))
+            }
+            TimerStatus::Paused => {
+                let remaining = longer(inner.paused_remaining, extra)?;
+                let duration = longer(inner.duration, extra)?;
+                inner.paused_remaining = remaining;
+                inner.duration = duration;
+                Ok((inner.snapshot(now), None))
+            }
+            TimerStatus::Finished | TimerStatus::Overtime => {
+                let deadline = end_of(now, extra)?;
+                let label = inner.label.take();
+                inner.generation += 1;
+                inner.reset();
+                inner.status = TimerStatus::Running;
+                inner.duration = extra;
+                inner.deadline = Some(deadline);
+                inner.label = label;
+                Ok((inner.snapshot(now), Some(inner.generation)))
+            }
+            TimerStatus::Idle => Ok((inner.snapshot(now), None)),
+        }
+    }
+
+    /// Runs the last countdown or sequence again from the start.
+    pub fn restart(&self) -> Option<Result<(TimerState, u64), String>> {
+        let (sequence, duration, label) = {
+            let inner = self.inner.lock().unwrap();
+            let sequence = match &inner.mode {
+                Mode::Sequence(run) => Some(run.sequence.clone()),
+                Mode::Countdown => None,
+                Mode::Pomodoro(_) | Mode::Stopwatch(_) => return None,
+            };
+            (sequence, inner.duration, inner.label.clone())
+        };
+
+        match sequence {
+            Some(sequence) => Some(self.start_sequence(sequence)),
+            None if !duration.is_zero() => Some(self.start_countdown(duration, label, None)),
+            None => None,
+        }
+    }
+
+    /// Turns overtime for plain countdowns on or off, including the one
+    /// that is running now.
+    pub fn set_overtime(&self, enabled: bool) {
+        self.inner.lock().unwrap().overtime = enabled;
+    }
+
+    /// Sets how many seconds before the end warnings are due.
+    pub fn set_warnings(&self, seconds_left: impl IntoIterator<Item = u64>) {
+        self.inner.lock().unwrap().warnings =
+            seconds_left.into_iter().map(Duration::from_secs).collect();
+    }
+
+    /// Clears the timer. A stopwatch that is still counting is only frozen,
+    /// so its time and laps can be copied, and a countdown in overtime keeps
+    /// its overrun on record; stopping again clears either.
+    pub fn stop(&self) -> TimerState {
+        let now = Instant::now();
+        let mut inner = self.inner.lock().unwrap();
+        inner.generation += 1;
+        let counting = matches!(inner.status, TimerStatus::Running | TimerStatus::Paused);
+        if counting && matches!(inner.mode, Mode::Stopwatch(_)) {
+            inner.elapsed_base = inner.elapsed(now);
+            inner.origin = None;
+            inner.status = TimerStatus::Finished;
+        } else if inner.status == TimerStatus::Overtime {
+            inner.overrun = inner.overrun(now);
+            inner.deadline = None;
+            inner.status = TimerStatus::Finished;
+        } else {
+            inner.reset();
+        }
+        inner.snapshot(now)
+    }
+
+    /// Freezes a running countdown. Anything else is left untouched.
+    pub fn pause(&self) -> TimerState {
+        let now = Instant::now();
+        let mut inner = self.inner.lock().unwrap();
+        if inner.status == TimerStatus::Running {
+            inner.paused_remaining = inner.remaining(now);
+            inner.elapsed_base = inner.elapsed(now);
+            inner.status = TimerStatus::Paused;
+            inner.deadline = None;
+            inner.origin = None;
+            inner.generation += 1;
+        }
+        inner.snapshot(now)
+    }
+
+    /// Continues a paused countdown from where it stopped. Returns the new
+    /// generation only if a countdown thread needs to be started.
+    pub fn resume(&self) -> Result<(TimerState, Option<u64>), String> {
+        let now = Instant::now();
+        let mut inner = self.inner.lock().unwrap();
+        if inner.status != TimerStatus::Paused {
+            return Ok((inner.snapshot(now), None));
+        }
+
+        if matches!(inner.mode, Mode::Stopwatch(_)) {
+            inner.origin = Some(now);
+        } else {
+            inner.deadline = Some(end_of(now, inner.paused_remaining)?);
+            // The pause pushed the end past the wall-clock time it aimed for.
+            inner.target = None;
+        }
+        inner.status = TimerStatus::Running;
+        inner.generation += 1;
+        Ok((inner.snapshot(now), Some(inner.generation)))
+    }
+
+    /// Re-reads the clock for the timer started as `generation`. Once the
+    /// deadline has passed, a Pomodoro session or sequence moves on to its
+    /// next phase, a countdown goes into overtime if enabled and anything
+    /// else is marked finished. Returns `None` once that timer is no longer
+    /// running.
+    pub fn tick(&self, generation: u64) -> Option<(TimerState, TickEvent)> {
+        let now = Instant::now();
+        let mut guard = self.inner.lock().unwrap();
+        let inner = &mut *guard;
+        let counting = matches!(inner.status, TimerStatus::Running | TimerStatus::Overtime);
+        if inner.generation != generation || !counting {
+            return None;
+        }
+
+        // Sub-millisecond leftovers count as done; we'd only wake up again
+        // a whole second later for them. The stopwatch and overtime have no
+        // end at all.
+        if matches!(inner.mode, Mode::Stopwatch(_))
+            || inner.status == TimerStatus::Overtime
+            || inner.remaining(now) >= Duration::from_millis(1)
+        {
+            let event = inner
+                .warning_due(now)
+                .map_or(TickEvent::Tick, TickEvent::Warning);
+            return Some((inner.snapshot(now), event));
+        }
+
+        let next = match &mut inner.mode {
+            Mode::Pomodoro(run) => {
+                run.advance();
+                Some(run.phase_duration())
+            }
+            Mode::Sequence(run) => run
+                .advance()
+                .then(|| Duration::from_secs(run.current().seconds)),
+            Mode::Countdown | Mode::Stopwatch(_) => None,
+        };
+
+        let event = match next {
+            Some(duration) => {
+                // Chain off the old deadline rather than `now`, so a late
+                // tick doesn't stretch the session.
+                let ended = inner.deadline.unwrap_or(now);
+                inner.duration = duration;
+                inner.deadline = Some(ended + duration);
+                TickEvent::PhaseChanged
+            }
+            None if inner.overtime && matches!(inner.mode, Mode::Countdown) => {
+                // Count the overrun from the zero we just reported.
+                inner.deadline = inner.deadline.map(|deadline| deadline.min(now));
+                inner.status = TimerStatus::Overtime;
+                TickEvent::Overtime
+            }
+            None => {
+                inner.status = TimerStatus::Finished;
+                inner.deadline = None;
+                TickEvent::Finished
+            }
+        };
+        Some((inner.snapshot(now), event))
+    }
+
+    pub fn saved(&self) -> SavedTimer {
+        let now = Instant::now();
+        let inner = self.inner.lock().unwrap();
+        let remaining_ms = inner.remaining(now).as_millis() as u64;
+        let overrun_ms = inner.overrun(now).as_millis() as u64;
+        SavedTimer {
+            status: inner.status,
+            duration_seconds: inner.duration.as_secs(),
+            deadline_unix_ms: inner
+                .deadline
+                .map(|_| (unix_ms(SystemTime::now()) + remaining_ms).saturating_sub(overrun_ms)),
+            paused_remaining_ms: (inner.status == TimerStatus::Paused).then_some(remaining_ms),
+            label: inner.label.clone(),
+            target: inner.target.clone(),
+            pomodoro: match &inner.mode {
+                Mode::Pomodoro(run) => Some(run.clone()),
+                _ => None,
+            },
+            sequence: match &inner.mode {
+                Mode::Sequence(run) => Some(run.clone()),
+                _ => None,
+            },
+            stopwatch: match &inner.mode {
+                Mode::Stopwatch(run) => Some(run.clone()),
+                _ => None,
+            },
+            elapsed_base_ms: inner.elapsed_base.as_millis() as u64,
+            started_unix_ms: inner
+                .origin
+                .map(|origin| unix_ms(SystemTime::now()).saturating_sub(ms_since(origin, now))),
+        }
+    }
+
+    /// Picks up a timer saved by a previous run. A running timer whose
+    /// deadline passed in the meantime comes back as finished while away.
+    /// Returns a generation if a countdown thread needs to be started.
+    pub fn restore(&self, saved: SavedTimer) -> Result<(TimerState, Option<u64>), String> {
+        saved.check()?;
+        let now = Instant::now();
+        let mut inner = self.inner.lock().unwrap();
+        inner.generation += 1;
+        inner.reset();
+
+        if let Some(run) = saved.stopwatch {
+            let generation = match (saved.status, saved.started_unix_ms) {
+                (TimerStatus::Running, Some(started)) => {
+                    // Time spent closed counts, just like for a countdown.
+                    let since_start = unix_ms(SystemTime::now()).saturating_sub(started);
+                    inner.status = TimerStatus::Running;
+                    inner.elapsed_base =
+                        Duration::from_millis(saved.elapsed_base_ms.saturating_add(since_start));
+                    inner.origin = Some(now);
+                    Some(inner.generation)
+                }
+                (TimerStatus::Paused, _) => {
+                    inner.status = TimerStatus::Paused;
+                    inner.elapsed_base = Duration::from_millis(saved.elapsed_base_ms);
+                    None
+                }
+                _ => return Ok((inner.snapshot(now), None)),
+            };
+            inner.mode = Mode::Stopwatch(run);
+            inner.label = saved.label;
+            return Ok((inner.snapshot(now), generation));
+        }
+
+        let mut generation = None;
+        let countdown = saved.pomodoro.is_none() && saved.sequence.is_none();
+        match (
+            saved.status,
+            saved.deadline_unix_ms,
+            saved.paused_remaining_ms,
+        ) {
+            (TimerStatus::Running | TimerStatus::Overtime, Some(deadline), _) => {
+                let now_ms = unix_ms(SystemTime::now());
+                let left = deadline.saturating_sub(now_ms);
+                // A countdown that ran out meanwhile is in overtime by now,
+                // if that's still on.
+                let over = Duration::from_millis(now_ms.saturating_sub(deadline));
+                let overtime_since = if inner.overtime && countdown {
+                    now.checked_sub(over)
+                } else {
+                    None
+                };
+                if left > 0 {
+                    inner.status = TimerStatus::Running;
+                    inner.deadline = Some(end_of(now, Duration::from_millis(left))?);
+                    generation = Some(inner.generation);
+                } else if let Some(deadline) = overtime_since {
+                    inner.status = TimerStatus::Overtime;
+                    inner.deadline = Some(deadline);
+                    generation = Some(inner.generation);
+                } else {
+                    inner.status = TimerStatus::Finished;
+                    inner.finished_while_away = true;
+                }
+            }
+            (TimerStatus::Paused, _, Some(remaining)) => {
+                inner.status = TimerStatus::Paused;
+                inner.paused_remaining = Duration::from_millis(remaining);
+            }
+            // Nothing worth bringing back for idle or already finished timers.
+            _ => return Ok((inner.snapshot(now), None)),
+        }
+
+        inner.duration = Duration::from_secs(saved.duration_seconds);
+        inner.label = saved.label;
+        inner.target = saved.target;
+        if let Some(run) = saved.pomodoro {
+            inner.mode = Mode::Pomodoro(run);
+        } else if let Some(run) = saved.sequence.filter(SequenceRun::is_valid) {
+            inner.mode = Mode::Sequence(run);
+        }
+        Ok((inner.snapshot(now), generation))
+    }
+}
+
+// The end of `duration` counted from `start`. Far-off ends are refused
+// before the engine changes, instead of overflowing the clock while it is
+// locked.
+fn end_of(start: Instant, duration: Duration) -> Result<Instant, String> {
+    start.checked_add(duration).ok_or_else(too_long)
+}
+
+fn longer(duration: Duration, extra: Duration) -> Result<Duration, String> {
+    duration.checked_add(extra).ok_or_else(too_long)
+}
+
+fn too_long() -> String {
+    "That is too long for a timer".to_string()
+}
+
+/// How long to sleep so the next tick lands just after a whole-second
+/// boundary of the remaining time (of the elapsed time for the stopwatch,
+/// of the overrun in overtime).
+pub fn until_next_second(state: &TimerState) -> Duration {
+    let ms = match (state.status, state.mode) {
+        (TimerStatus::Overtime, _) => 1000 - state.overrun_ms % 1000,
+        (_, TimerMode::Stopwatch) => 1000 - state.elapsed_ms % 1000,
+        _ => match state.remaining_ms % 1000 {
+            0 => 1000,
+            ms => ms,
+        },
+    };
+    Duration::from_millis(ms)
+}
+
+fn ms_since(earlier: Instant, now: Instant) -> u64 {
+    now.saturating_duration_since(earlier).as_millis() as u64
+}
+
+fn unix_ms(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn saved(status: TimerStatus) -> SavedTimer {
+        SavedTimer {
+            status,
+            duration_seconds: 300,
+            deadline_unix_ms: None,
+            paused_remaining_ms: None,
+            label: Some("Tea".to_string()),
+            target: None,
+            pomodoro: None,
+            sequence: None,
+            stopwatch: None,
+            elapsed_base_ms: 0,
+            started_unix_ms: None,
+        }
+    }
+
+    // Moves the running countdown's end to `left` from now.
+    fn ends_in(engine: &TimerEngine, left: Duration) {
+        engine.inner.lock().unwrap().deadline = Some(Instant::now() + left);
+    }
+
+    #[test]
+    fn finishes_once_the_deadline_passes() {
+        let engine = TimerEngine::new();
+        let (state, generation) = engine.start(60, None).unwrap();
+        assert_eq!(state.status, TimerStatus::Running);
+        assert_eq!(state.remaining_seconds, 60);
+
+        ends_in(&engine, Duration::from_millis(1500));
+        let (state, event) = engine.tick(generation).unwrap();
+        assert_eq!(event, TickEvent::Tick);
+        assert_eq!(state.remaining_seconds, 2);
+
+        ends_in(&engine, Duration::ZERO);
+        let (state, event) = engine.tick(generation).unwrap();
+        assert_eq!(event, TickEvent::Finished);
+        assert_eq!(state.status, TimerStatus::Finished);
+        assert_eq!(state.remaining_ms, 0);
+        assert!(engine.tick(generation).is_none());
+    }
+
+    #[test]
+    fn a_pomodoro_moves_on_to_the_next_phase() {
+        let engine = TimerEngine::new();
+        let (state, generation) = engine.start_pomodoro(PomodoroConfig::default()).unwrap();
+        assert_eq!(state.mode, TimerMode::Pomodoro);
+        assert_eq!(state.duration_seconds, 25 * 60);
+
+        ends_in(&engine, Duration::ZERO);
+        let (state, event) = engine.tick(generation).unwrap();
+        assert_eq!(event, TickEvent::PhaseChanged);
+        assert_eq!(state.status, TimerStatus::Running);
+        assert_eq!(state.duration_seconds, 5 * 60);
+        assert!(engine.tick(generation).is_some());
+    }
+
+    #[test]
+    fn counts_on_into_overtime_when_enabled() {
+        let engine = TimerEngine::new();
+        engine.set_overtime(true);
+        let (_, generation) = engine.start(60, None).unwrap();
+        ends_in(&engine, Duration::ZERO);
+        let (state, event) = engine.tick(generation).unwrap();
+        assert_eq!(event, TickEvent::Overtime);
+        assert_eq!(state.status, TimerStatus::Overtime);
+
+        engine.inner.lock().unwrap().deadline = Some(Instant::now() - Duration::from_secs(5));
+        let (state, event) = engine.tick(generation).unwrap();
+        assert_eq!(event, TickEvent::Tick);
+        assert!((5000..6000).contains(&state.overrun_ms));
+
+        let stopped = engine.stop();
+        assert_eq!(stopped.status, TimerStatus::Finished);
+        assert!(stopped.overrun_ms >= 5000);
+        assert!(engine.tick(generation).is_none());
+    }
+
+    #[test]
+    fn extending_a_finished_countdown_starts_it_again() {
+        let engine = TimerEngine::new();
+        let (_, generation) = engine.start(60, Some("Tea".to_string())).unwrap();
+        ends_in(&engine, Duration::ZERO);
+        engine.tick(generation).unwrap();
+
+        let (state, generation) = engine.extend(Duration::from_secs(300)).unwrap();
+        assert_eq!(state.status, TimerStatus::Running);
+        assert_eq!(state.duration_seconds, 300);
+        assert_eq!(state.label.as_deref(), Some("Tea"));
+        assert!(engine.tick(generation.unwrap()).is_some());
+    }
+
+    #[test]
+    fn extending_an_idle_timer_does_nothing() {
+        let engine = TimerEngine::new();
+        let (state, generation) = engine.extend(Duration::from_secs(60)).unwrap();
+        assert_eq!(state.status, TimerStatus::Idle);
+        assert_eq!(generation, None);
+    }
+
+    #[test]
+    fn warns_on_the_tick_that_ends_the_warned_second() {
+        let engine = TimerEngine::new();
+        engine.set_warnings([60]);
+        let (_, generation) = engine.start(120, None).unwrap();
+
+        ends_in(&engine, Duration::from_millis(60_500));
+        assert_eq!(engine.tick(generation).unwrap().1, TickEvent::Tick);
+        ends_in(&engine, Duration::from_millis(59_500));
+        assert_eq!(engine.tick(generation).unwrap().1, TickEvent::Warning(60));
+        ends_in(&engine, Duration::from_millis(58_500));
+        assert_eq!(engine.tick(generation).unwrap().1, TickEvent::Tick);
+    }
+
+    #[test]
+    fn no_warning_for_a_timer_not_longer_than_it() {
+        let engine = TimerEngine::new();
+        engine.set_warnings([60]);
+        let (_, generation) = engine.start(60, None).unwrap();
+        ends_in(&engine, Duration::from_millis(59_500));
+        assert_eq!(engine.tick(generation).unwrap().1, TickEvent::Tick);
+    }
+
+    #[test]
+    fn a_zero_countdown_stays_idle() {
+        let engine = TimerEngine::new();
+        let (state, _) = engine.start(0, None).unwrap();
+        assert_eq!(state.status, TimerStatus::Idle);
+    }
+
+    #[test]
+    fn stopping_ends_the_countdown_thread() {
+        let engine = TimerEngine::new();
+        let (_, generation) = engine.start(60, None).unwrap();
+        assert_eq!(engine.stop().status, TimerStatus::Idle);
+        assert!(engine.tick(generation).is_none());
+    }
+
+    #[test]
+    fn a_new_start_ends_the_old_countdown_thread() {
+        let engine = TimerEngine::new();
+        let (_, old) = engine.start(60, None).unwrap();
+        let (_, new) = engine.start(30, None).unwrap();
+        assert!(engine.tick(old).is_none());
+        assert!(engine.tick(new).is_some());
+    }
+
+    #[test]
+    fn pausing_keeps_the_time_left() {
+        let engine = TimerEngine::new();
+        let (_, generation) = engine.start(60, None).unwrap();
+        ends_in(&engine, Duration::from_millis(42_500));
+
+        let state = engine.pause();
+        assert_eq!(state.status, TimerStatus::Paused);
+        assert!((42_400..=42_500).contains(&state.remaining_ms));
+        assert!(engine.tick(generation).is_none());
+        assert_eq!(engine.state().remaining_ms, state.remaining_ms);
+
+        let (resumed, resumed_generation) = engine.resume().unwrap();
+        assert_eq!(resumed.status, TimerStatus::Running);
+        assert_eq!(resumed.remaining_seconds, 43);
+        assert!(engine.tick(resumed_generation.unwrap()).is_some());
+    }
+
+    #[test]
+    fn resuming_a_running_timer_does_nothing() {
+        let engine = TimerEngine::new();
+        engine.start(60, None).unwrap();
+        let (state, generation) = engine.resume().unwrap();
+        assert_eq!(state.status, TimerStatus::Running);
+        assert_eq!(generation, None);
+    }
+
+    #[test]
+    fn far_off_ends_are_refused_without_touching_the_timer() {
+        let engine = TimerEngine::new();
+        let (_, generation) = engine.start(60, None).unwrap();
+        assert!(engine.start(u64::MAX, None).is_err());
+        assert!(engine
+            .start_until(Duration::MAX, None, "never".to_string())
+            .is_err());
+        assert!(engine.extend(Duration::MAX).is_err());
+
+        let state = engine.state();
+        assert_eq!(state.status, TimerStatus::Running);
+        assert_eq!(state.duration_seconds, 60);
+        assert!(engine.tick(generation).is_some());
+    }
+
+    #[test]
+    fn stopping_a_stopwatch_freezes_it_before_clearing_it() {
+        let engine = TimerEngine::new();
+        let (_, generation) = engine.start_stopwatch();
+        engine.inner.lock().unwrap().elapsed_base = Duration::from_secs(90);
+        let state = engine.record_lap();
+        assert_eq!(state.stopwatch.unwrap().laps.len(), 1);
+
+        let frozen = engine.stop();
+        assert_eq!(frozen.status, TimerStatus::Finished);
+        assert!(frozen.elapsed_ms >= 90_000);
+        assert!(engine.tick(generation).is_none());
+        assert_eq!(engine.stop().status, TimerStatus::Idle);
+    }
+
+    #[test]
+    fn ticks_land_just_after_each_whole_second() {
+        let state = |remaining_ms| TimerState {
+            status: TimerStatus::Running,
+            mode: TimerMode::Countdown,
+            duration_seconds: 60,
+            remaining_seconds: u64::div_ceil(remaining_ms, 1000),
+            remaining_ms,
+            elapsed_ms: 0,
+            overrun_ms: 0,
+            label: None,
+            target: None,
+            finished_while_away: false,
+            pomodoro: None,
+            sequence: None,
+            stopwatch: None,
+        };
+        assert_eq!(until_next_second(&state(2300)), Duration::from_millis(300));
+        assert_eq!(until_next_second(&state(2000)), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn restores_a_timer_that_ran_out_while_away_as_finished() {
+        let engine = TimerEngine::new();
+        let mut timer = saved(TimerStatus::Running);
+        timer.deadline_unix_ms = Some(unix_ms(SystemTime::now()) - 1000);
+        let (state, generation) = engine.restore(timer).unwrap();
+        assert_eq!(state.status, TimerStatus::Finished);
+        assert!(state.finished_while_away);
+        assert_eq!(state.label.as_deref(), Some("Tea"));
+        assert_eq!(generation, None);
+    }
+
+    #[test]
+    fn restores_a_running_timer_with_its_time_left() {
+        let engine = TimerEngine::new();
+        let mut timer = saved(TimerStatus::Running);
+        timer.deadline_unix_ms = Some(unix_ms(SystemTime::now()) + 120_000);
+        let (state, generation) = engine.restore(timer).unwrap();
+        assert_eq!(state.status, TimerStatus::Running);
+        assert!((119..=120).contains(&state.remaining_seconds));
+        assert!(generation.is_some());
+    }
+
+    #[test]
+    fn restores_a_paused_timer_as_it_was() {
+        let engine = TimerEngine::new();
+        let mut timer = saved(TimerStatus::Paused);
+        timer.paused_remaining_ms = Some(90_500);
+        let (state, generation) = engine.restore(timer).unwrap();
+        assert_eq!(state.status, TimerStatus::Paused);
+        assert_eq!(state.remaining_ms, 90_500);
+        assert_eq!(state.remaining_seconds, 91);
+        assert_eq!(generation, None);
+    }
+
+    #[test]
+    fn saves_a_running_timer_by_its_wall_clock_deadline() {
+        let engine = TimerEngine::new();
+        engine.start(60, Some("Tea".to_string())).unwrap();
+        let before = unix_ms(SystemTime::now());
+        let saved = engine.saved();
+        assert_eq!(saved.status, TimerStatus::Running);
+        assert_eq!(saved.label.as_deref(), Some("Tea"));
+        let deadline = saved.deadline_unix_ms.unwrap();
+        assert!((before + 59_000..=before + 61_000).contains(&deadline));
+        assert_eq!(saved.paused_remaining_ms, None);
+    }
+
+    #[test]
+    fn refuses_saved_timers_longer_than_any_timer_can_run() {
+        let engine = TimerEngine::new();
+        let mut timer = saved(TimerStatus::Paused);
+        timer.paused_remaining_ms = Some(u64::MAX);
+        assert!(engine.restore(timer).is_err());
+
+        let mut timer = saved(TimerStatus::Running);
+        timer.duration_seconds = MAX_SECONDS + 1;
+        timer.deadline_unix_ms = Some(unix_ms(SystemTime::now()) + 1000);
+        assert!(engine.restore(timer).is_err());
+        assert_eq!(engine.state().status, TimerStatus::Idle);
+    }
+
+    #[test]
+    fn refuses_saved_pomodoro_sessions_with_empty_phases() {
+        let engine = TimerEngine::new();
+        let mut timer = saved(TimerStatus::Paused);
+        timer.paused_remaining_ms = Some(60_000);
+        timer.pomodoro = Some(PomodoroRun::new(PomodoroConfig {
+            short_break_minutes: 0,
+            ..PomodoroConfig::default()
+        }));
+        assert!(engine.restore(timer).is_err());
+        assert_eq!(engine.state().status, TimerStatus::Idle);
+    }
+}
@@ -0,0 +1,256 @@
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, EventTarget, Manager, WebviewUrl, WebviewWindowBuilder};
+
+use crate::presentation::PRESENTATION_LABEL;
+use crate::timer::{TimerEngine, TimerState, TimerStatus};
+
+/// The timer of the main window, which is always there.
+pub const MAIN: &str = "main";
+const MAIN_TITLE: &str = "Timer";
+const ID_PREFIX: &str = "timer-";
+
+/// A timer and the window it is shown in; both go by the same id.
+#[derive(Clone)]
+pub struct Timer {
+    pub id: String,
+    pub title: String,
+    pub engine: Arc<TimerEngine>,
+}
+
+impl Timer {
+    pub fn info(&self) -> TimerInfo {
+        TimerInfo {
+            id: self.id.clone(),
+            title: self.title.clone(),
+            state: self.engine.state(),
+        }
+    }
+}
+
+/// A timer as listed to the webview.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimerInfo {
+    pub id: String,
+    pub title: String,
+    pub state: TimerState,
+}
+
+/// All running timers: the main one plus any added at runtime, each with
+/// its own window. Managed as Tauri state.
+pub struct TimerRegistry {
+    main: Timer,
+    others: Mutex<Vec<Timer>>,
+    // Numbers the added timers, so ids aren't reused after a close.
+    created: Mutex<u32>,
+}
+
+impl Default for TimerRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TimerRegistry {
+    pub fn new() -> Self {
+        Self {
+            main: Timer {
+                id: MAIN.to_string(),
+                title: MAIN_TITLE.to_string(),
+                engine: Arc::new(TimerEngine::new()),
+            },
+            others: Mutex::new(Vec::new()),
+            created: Mutex::new(1),
+        }
+    }
+
+    pub fn main(&self) -> Timer {
+        self.main.clone()
+    }
+
+    /// The main timer first, then the others in the order they were added.
+    pub fn all(&self) -> Vec<Timer> {
+        let mut timers = vec![self.main()];
+        timers.extend(self.others.lock().unwrap().iter().cloned());
+        timers
+    }
+
+    pub fn get(&self, id: &str) -> Result<Timer, String> {
+        if id == MAIN {
+            return Ok(self.main());
+        }
+        self.others
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|timer| timer.id == id)
+            .cloned()
+            .ok_or_else(|| format!("There is no timer \"{}\"", id))
+    }
+
+    /// The timer shown in the window labelled `window_label`.
+    pub fn shown_in(&self, window_label: &str) -> Result<Timer, String> {
+        self.get(timer_id(window_label))
+    }
+
+    /// The timer the tray icon, its Pause and Stop entries and the global
+    /// shortcuts act on: the main one, unless it is idle and another one
+    /// isn't.
+    pub fn foremost(&self) -> Timer {
+        if self.main.engine.state().status != TimerStatus::Idle {
+            return self.main();
+        }
+        self.others
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|timer| timer.engine.state().status != TimerStatus::Idle)
+            .cloned()
+            .unwrap_or_else(|| self.main())
+    }
+
+    /// Adds an idle timer, called `title` or numbered.
+    pub fn add(&self, title: Option<String>) -> Timer {
+        let number = {
+            let mut created = self.created.lock().unwrap();
+            *created += 1;
+            *created
+        };
+        let timer = Timer {
+            id: format!("{}{}", ID_PREFIX, number),
+            title: title
+                .map(|title| title.trim().to_string())
+                .filter(|title| !title.is_empty())
+                .unwrap_or_else(|| format!("{} {}", MAIN_TITLE, number)),
+            engine: Arc::new(TimerEngine::new()),
+        };
+        self.others.lock().unwrap().push(timer.clone());
+        timer
+    }
+
+    /// Takes a timer out of the registry. The main one can't be removed.
+    pub fn remove(&self, id: &str) -> Option<Timer> {
+        let mut others = self.others.lock().unwrap();
+        let index = others.iter().position(|timer| timer.id == id)?;
+        Some(others.remove(index))
+    }
+}
+
+/// The timer a window shows: its own, or the main one for the
+/// presentation window.
+pub fn timer_id(window_label: &str) -> &str {
+    if window_label == PRESENTATION_LABEL {
+        MAIN
+    } else {
+        window_label
+    }
+}
+
+/// Sends one of a timer's events to the windows showing that timer only.
+pub fn emit<S: Serialize + Clone>(
+    app: &AppHandle,
+    id: &str,
+    event: &str,
+    payload: S,
+) -> tauri::Result<()> {
+    app.emit_filter(event, payload, |target| match target {
+        EventTarget::WebviewWindow { label } => timer_id(label) == id,
+        _ => false,
+    })
+}
+
+/// Opens the window of a newly added timer.
+pub fn open_window(app: &AppHandle, timer: &Timer) -> Result<(), String> {
+    WebviewWindowBuilder::new(
+        app,
+        &timer.id,
+        WebviewUrl::App(PathBuf::from("index.html?timer")),
+    )
+    .title(&timer.title)
+    .inner_size(300.0, 300.0)
+    .min_inner_size(180.0, 180.0)
+    .max_inner_size(700.0, 700.0)
+    .build()
+    .map(|_| ())
+    .map_err(|e| format!("Failed to open timer window: {}", e))
+}
+
+/// Brings a timer's window to the front, showing it if it was hidden.
+pub fn focus_window(app: &AppHandle, id: &str) -> Result<(), String> {
+    let window = app
+        .get_webview_window(id)
+        .ok_or_else(|| format!("Timer \"{}\" has no window", id))?;
+    window
+        .unminimize()
+        .and_then(|_| window.show())
+        .and_then(|_| window.set_focus())
+        .map_err(|e| format!("Failed to focus timer window: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn added_timers_are_numbered_or_named_by_their_trimmed_title() {
+        let timers = TimerRegistry::new();
+        let first = timers.add(None);
+        assert_eq!(first.id, "timer-2");
+        assert_eq!(first.title, "Timer 2");
+
+        let named = timers.add(Some("  Tea ".to_string()));
+        assert_eq!(named.id, "timer-3");
+        assert_eq!(named.title, "Tea");
+
+        let blank = timers.add(Some("   ".to_string()));
+        assert_eq!(blank.title, "Timer 4");
+    }
+
+    #[test]
+    fn removed_timers_are_gone_and_their_numbers_not_reused() {
+        let timers = TimerRegistry::new();
+        let added = timers.add(None);
+        assert!(timers.remove(&added.id).is_some());
+        assert!(timers.get(&added.id).is_err());
+        assert!(timers.remove(&added.id).is_none());
+        assert_eq!(timers.add(None).id, "timer-3");
+    }
+
+    #[test]
+    fn the_main_timer_cant_be_removed() {
+        let timers = TimerRegistry::new();
+        assert!(timers.remove(MAIN).is_none());
+        assert_eq!(timers.all().len(), 1);
+    }
+
+    #[test]
+    fn the_foremost_timer_is_the_main_one_unless_only_another_runs() {
+        let timers = TimerRegistry::new();
+        let first = timers.add(None);
+        let second = timers.add(None);
+        assert_eq!(timers.foremost().id, MAIN);
+
+        second.engine.start(60, None).unwrap();
+        assert_eq!(timers.foremost().id, second.id);
+        first.engine.start(60, None).unwrap();
+        assert_eq!(timers.foremost().id, first.id);
+
+        timers.main().engine.start(60, None).unwrap();
+        assert_eq!(timers.foremost().id, MAIN);
+    }
+
+    #[test]
+    fn each_window_shows_its_own_timer_and_the_presentation_the_main_one() {
+        let timers = TimerRegistry::new();
+        let added = timers.add(None);
+        assert_eq!(timer_id(PRESENTATION_LABEL), MAIN);
+        assert_eq!(timer_id(&added.id), added.id);
+        assert_eq!(timers.shown_in(PRESENTATION_LABEL).unwrap().id, MAIN);
+        assert_eq!(timers.shown_in(MAIN).unwrap().id, MAIN);
+        assert_eq!(timers.shown_in(&added.id).unwrap().id, added.id);
+        assert!(timers.shown_in("settings").is_err());
+    }
+}
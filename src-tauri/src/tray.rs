@@ -0,0 +1,441 @@
+use std::sync::Mutex;
+
+use tauri::image::Image;
+use tauri::menu::{Menu, MenuItem, PredefinedMenuItem, Submenu};
+use tauri::tray::{TrayIcon, TrayIconBuilder};
+use tauri::{AppHandle, Manager, Wry};
+
+use crate::pie::{self, Sector};
+use crate::settings::SettingsStore;
+use crate::timer::{TimerMode, TimerState, TimerStatus};
+use crate::timers::{Timer, TimerRegistry};
+
+const TRAY_ID: &str = "main";
+const PAUSE_ITEM: &str = "pause";
+const STOP_ITEM: &str = "stop";
+const EXTEND_ITEM: &str = "extend";
+const RESTART_ITEM: &str = "restart";
+const WINDOW_ITEM: &str = "window";
+const QUICK_START_PREFIX: &str = "start-";
+const QUICK_START_MINUTES: [u64; 5] = [5, 10, 15, 25, 45];
+const FOCUS_PREFIX: &str = "focus-";
+const IDLE_TOOLTIP: &str = "Visual Countdown";
+
+/// An entry picked from the tray menu.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrayAction {
+    /// Pauses a running timer or resumes a paused one.
+    PauseResume,
+    Stop,
+    /// Adds five minutes, starting a finished timer again.
+    Extend,
+    /// Runs the last countdown or sequence again from the start.
+    Restart,
+    /// Shows the window, or hides it when it is already visible.
+    ToggleWindow,
+    /// Starts a plain countdown of this many minutes.
+    QuickStart(u64),
+    /// Brings the window of the timer with this id to the front.
+    FocusTimer(String),
+}
+
+/// Managed as Tauri state so the icon and menu can follow the timer.
+pub struct Tray {
+    icon: TrayIcon,
+    pause: MenuItem<Wry>,
+    stop: MenuItem<Wry>,
+    extend: MenuItem<Wry>,
+    restart: MenuItem<Wry>,
+    /// One entry per timer, by timer id.
+    timers: Submenu<Wry>,
+    entries: Mutex<Vec<(String, MenuItem<Wry>)>>,
+    /// The sector the icon currently shows.
+    drawn: Mutex<Sector>,
+}
+
+/// Builds the tray icon and its menu, and hands picked entries to
+/// `on_action`. "Quit" is handled by Tauri itself.
+pub fn init(
+    app: &AppHandle,
+    on_action: impl Fn(&AppHandle, TrayAction) + Send + Sync + 'static,
+) -> Result<(), String> {
+    build(app, on_action).map_err(|e| format!("Failed to create tray icon: {}", e))
+}
+
+fn build(
+    app: &AppHandle,
+    on_action: impl Fn(&AppHandle, TrayAction) + Send + Sync + 'static,
+) -> tauri::Result<()> {
+    let pause = MenuItem::with_id(app, PAUSE_ITEM, "Pause", false, None::<&str>)?;
+    let stop = MenuItem::with_id(app, STOP_ITEM, "Stop", false, None::<&str>)?;
+    let extend = MenuItem::with_id(app, EXTEND_ITEM, "+5 min", false, None::<&str>)?;
+    let restart = MenuItem::with_id(app, RESTART_ITEM, "Restart", false, None::<&str>)?;
+    let timers = Submenu::new(app, "Timers", true)?;
+    let window = MenuItem::with_id(app, WINDOW_ITEM, "Show/Hide Window", true, None::<&str>)?;
+    let quick_starts = QUICK_START_MINUTES
+        .iter()
+        .map(|minutes| {
+            MenuItem::with_id(
+                app,
+                format!("{}{}", QUICK_START_PREFIX, minutes),
+                format!("Start {} min", minutes),
+                true,
+                None::<&str>,
+            )
+        })
+        .collect::<tauri::Result<Vec<_>>>()?;
+
+    let menu = Menu::with_items(
+        app,
+        &[
+            &pause,
+            &stop,
+            &extend,
+            &restart,
+            &timers,
+            &PredefinedMenuItem::separator(app)?,
+        ],
+    )?;
+    for item in &quick_starts {
+        menu.append(item)?;
+    }
+    menu.append_items(&[
+        &PredefinedMenuItem::separator(app)?,
+        &window,
+        &PredefinedMenuItem::quit(app, None)?,
+    ])?;
+
+    let idle = Sector::default();
+    let icon = TrayIconBuilder::with_id(TRAY_ID)
+        .icon(icon_image(&idle))
+        .menu(&menu)
+        .tooltip(IDLE_TOOLTIP)
+        .on_menu_event(move |app, event| {
+            let id = event.id().as_ref();
+            let action = match id {
+                PAUSE_ITEM => TrayAction::PauseResume,
+                STOP_ITEM => TrayAction::Stop,
+                EXTEND_ITEM => TrayAction::Extend,
+                RESTART_ITEM => TrayAction::Restart,
+                WINDOW_ITEM => TrayAction::ToggleWindow,
+                _ if id.starts_with(FOCUS_PREFIX) => {
+                    TrayAction::FocusTimer(id[FOCUS_PREFIX.len()..].to_string())
+                }
+                _ => match id
+                    .strip_prefix(QUICK_START_PREFIX)
+                    .and_then(|minutes| minutes.parse().ok())
+                {
+                    Some(minutes) => TrayAction::QuickStart(minutes),
+                    None => return,
+                },
+            };
+            on_action(app, action);
+        })
+        .build(app)?;
+
+    app.manage(Tray {
+        icon,
+        pause,
+        stop,
+        extend,
+        restart,
+        timers,
+        entries: Mutex::new(Vec::new()),
+        drawn: Mutex::new(idle),
+    });
+    Ok(())
+}
+
+fn icon_image(sector: &Sector) -> Image<'static> {
+    Image::new_owned(pie::render(sector), pie::ICON_SIZE, pie::ICON_SIZE)
+}
+
+/// Whether the tray icon could be created, so there's a way back to a
+/// hidden window.
+pub fn is_shown(app: &AppHandle) -> bool {
+    app.try_state::<Tray>().is_some()
+}
+
+/// Draws the foremost timer's sector as the icon, puts the time of every
+/// active timer in the tooltip and lists all timers in the menu. Pause,
+/// Stop, "+5 min" and Restart follow the foremost timer, offered only when
+/// they make sense for its status.
+///
+/// Countdown threads call this too. Menu calls from them would wait for the
+/// main thread, so the whole update runs there instead, and the entries are
+/// only ever locked on the main thread.
+pub fn update(app: &AppHandle) {
+    let handle = app.clone();
+    if let Err(e) = app.run_on_main_thread(move || refresh(&handle)) {
+        eprintln!("Failed to update tray icon: {}", e);
+    }
+}
+
+fn refresh(app: &AppHandle) {
+    let Some(tray) = app.try_state::<Tray>() else {
+        return;
+    };
+    let registry = app.state::<TimerRegistry>();
+    let timers = registry
+        .all()
+        .into_iter()
+        .map(|timer| {
+            let state = timer.engine.state();
+            (timer, state)
+        })
+        .collect::<Vec<_>>();
+    if let Err(e) = update_entries(app, &tray, &timers) {
+        eprintln!("Failed to update tray menu: {}", e);
+    }
+
+    let state = &registry.foremost().engine.state();
+    let span_seconds = u64::from(app.state::<SettingsStore>().get().dial_span_minutes) * 60;
+    let sector = pie::sector(state, span_seconds);
+    // Most ticks move the sector by less than a degree; skip redrawing those
+    let redraw = {
+        let mut drawn = tray.drawn.lock().unwrap();
+        let changed = *drawn != sector;
+        *drawn = sector;
+        changed
+    };
+    if redraw {
+        if let Err(e) = tray.icon.set_icon(Some(icon_image(&sector))) {
+            eprintln!("Failed to update tray icon: {}", e);
+        }
+    }
+    let pause_text = if state.status == TimerStatus::Paused {
+        "Resume"
+    } else {
+        "Pause"
+    };
+    let can_pause = matches!(state.status, TimerStatus::Running | TimerStatus::Paused);
+    // Once a countdown has run out it can be given more time or run again
+    let ran_out = matches!(state.status, TimerStatus::Finished | TimerStatus::Overtime)
+        && state.mode != TimerMode::Stopwatch;
+    let result = tray
+        .icon
+        .set_tooltip(Some(tooltip(&timers, state)))
+        .and_then(|_| tray.pause.set_text(pause_text))
+        .and_then(|_| tray.pause.set_enabled(can_pause))
+        .and_then(|_| tray.stop.set_enabled(state.status != TimerStatus::Idle))
+        .and_then(|_| tray.extend.set_enabled(ran_out))
+        .and_then(|_| tray.restart.set_enabled(ran_out));
+    if let Err(e) = result {
+        eprintln!("Failed to update tray icon: {}", e);
+    }
+}
+
+// The entries are rebuilt only when timers come or go; ticks just relabel
+// them.
+fn update_entries(
+    app: &AppHandle,
+    tray: &Tray,
+    timers: &[(Timer, TimerState)],
+) -> tauri::Result<()> {
+    let mut entries = tray.entries.lock().unwrap();
+    let same = entries.len() == timers.len()
+        && entries
+            .iter()
+            .zip(timers)
+            .all(|((id, _), (timer, _))| *id == timer.id);
+    if !same {
+        for (_, item) in entries.drain(..) {
+            tray.timers.remove(&item)?;
+        }
+        for (timer, _) in timers {
+            let item = MenuItem::with_id(
+                app,
+                format!("{}{}", FOCUS_PREFIX, timer.id),
+                &timer.title,
+                true,
+                None::<&str>,
+            )?;
+            tray.timers.append(&item)?;
+            entries.push((timer.id.clone(), item));
+        }
+    }
+    for ((_, item), (timer, state)) in entries.iter().zip(timers) {
+        item.set_text(entry(timer, state))?;
+    }
+    Ok(())
+}
+
+// Every active timer when several are, or else the foremost one.
+fn tooltip(timers: &[(Timer, TimerState)], foremost: &TimerState) -> String {
+    let active = timers
+        .iter()
+        .filter(|(_, state)| state.status != TimerStatus::Idle)
+        .map(|(timer, state)| entry(timer, state))
+        .collect::<Vec<_>>();
+    if active.len() > 1 {
+        active.join("\n")
+    } else {
+        describe(foremost)
+    }
+}
+
+fn describe(state: &TimerState) -> String {
+    let Some(time) = time(state) else {
+        return IDLE_TOOLTIP.to_string();
+    };
+    match &state.label {
+        Some(label) => format!("{} · {}", label, time),
+        None => time,
+    }
+}
+
+// One timer among several: named by its label, or else its title.
+fn entry(timer: &Timer, state: &TimerState) -> String {
+    let name = state.label.as_deref().unwrap_or(&timer.title);
+    match time(state) {
+        Some(time) => format!("{} · {}", name, time),
+        None => name.to_string(),
+    }
+}
+
+fn time(state: &TimerState) -> Option<String> {
+    let time = match state.status {
+        TimerStatus::Idle => return None,
+        TimerStatus::Overtime => format!("+{} over", clock(state.overrun_ms / 1000)),
+        _ if state.mode == TimerMode::Stopwatch => clock(state.elapsed_ms / 1000),
+        TimerStatus::Finished => "Finished".to_string(),
+        _ => format!("{} left", clock(state.remaining_seconds)),
+    };
+    Some(if state.status == TimerStatus::Paused {
+        format!("{} (paused)", time)
+    } else {
+        time
+    })
+}
+
+// "4:05" or "1:02:05", like the caption under the dial.
+fn clock(seconds: u64) -> String {
+    let (hours, minutes, seconds) = (seconds / 3600, seconds % 3600 / 60, seconds % 60);
+    if hours > 0 {
+        format!("{}:{:02}:{:02}", hours, minutes, seconds)
+    } else {
+        format!("{}:{:02}", minutes, seconds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::timer::TimerEngine;
+
+    fn state(status: TimerStatus, mode: TimerMode) -> TimerState {
+        TimerState {
+            status,
+            mode,
+            duration_seconds: 0,
+            remaining_seconds: 0,
+            remaining_ms: 0,
+            elapsed_ms: 0,
+            overrun_ms: 0,
+            label: None,
+            target: None,
+            finished_while_away: false,
+            pomodoro: None,
+            sequence: None,
+            stopwatch: None,
+        }
+    }
+
+    fn countdown(status: TimerStatus, remaining_seconds: u64) -> TimerState {
+        TimerState {
+            remaining_seconds,
+            ..state(status, TimerMode::Countdown)
+        }
+    }
+
+    fn timer(id: &str, title: &str) -> Timer {
+        Timer {
+            id: id.to_string(),
+            title: title.to_string(),
+            engine: Arc::new(TimerEngine::new()),
+        }
+    }
+
+    #[test]
+    fn clock_rolls_over_into_hours() {
+        assert_eq!(clock(0), "0:00");
+        assert_eq!(clock(245), "4:05");
+        assert_eq!(clock(3599), "59:59");
+        assert_eq!(clock(3600), "1:00:00");
+        assert_eq!(clock(3725), "1:02:05");
+    }
+
+    #[test]
+    fn describes_each_status() {
+        let idle = state(TimerStatus::Idle, TimerMode::Countdown);
+        assert_eq!(describe(&idle), IDLE_TOOLTIP);
+        assert_eq!(describe(&countdown(TimerStatus::Running, 245)), "4:05 left");
+        assert_eq!(
+            describe(&countdown(TimerStatus::Paused, 245)),
+            "4:05 left (paused)"
+        );
+        assert_eq!(describe(&countdown(TimerStatus::Finished, 0)), "Finished");
+
+        let overtime = TimerState {
+            overrun_ms: 65_000,
+            ..state(TimerStatus::Overtime, TimerMode::Countdown)
+        };
+        assert_eq!(describe(&overtime), "+1:05 over");
+
+        let stopwatch = TimerState {
+            elapsed_ms: 3_725_400,
+            ..state(TimerStatus::Paused, TimerMode::Stopwatch)
+        };
+        assert_eq!(describe(&stopwatch), "1:02:05 (paused)");
+    }
+
+    #[test]
+    fn a_label_goes_before_the_time() {
+        let tea = TimerState {
+            label: Some("Tea".to_string()),
+            ..countdown(TimerStatus::Running, 180)
+        };
+        assert_eq!(describe(&tea), "Tea · 3:00 left");
+    }
+
+    #[test]
+    fn entries_name_the_timer_by_its_label_or_title() {
+        let second = timer("timer-2", "Timer 2");
+        let idle = state(TimerStatus::Idle, TimerMode::Countdown);
+        assert_eq!(entry(&second, &idle), "Timer 2");
+        assert_eq!(
+            entry(&second, &countdown(TimerStatus::Running, 60)),
+            "Timer 2 · 1:00 left"
+        );
+        let tea = TimerState {
+            label: Some("Tea".to_string()),
+            ..countdown(TimerStatus::Running, 60)
+        };
+        assert_eq!(entry(&second, &tea), "Tea · 1:00 left");
+    }
+
+    #[test]
+    fn the_tooltip_lists_every_active_timer_when_several_are() {
+        let running = countdown(TimerStatus::Running, 60);
+        let timers = [
+            (timer("main", "Timer"), running.clone()),
+            (
+                timer("timer-2", "Timer 2"),
+                countdown(TimerStatus::Paused, 90),
+            ),
+            (
+                timer("timer-3", "Timer 3"),
+                state(TimerStatus::Idle, TimerMode::Countdown),
+            ),
+        ];
+        assert_eq!(
+            tooltip(&timers, &running),
+            "Timer · 1:00 left\nTimer 2 · 1:30 left (paused)"
+        );
+
+        // Only one of them running: just its time, as with a single timer
+        assert_eq!(tooltip(&timers[..1], &running), "1:00 left");
+    }
+}
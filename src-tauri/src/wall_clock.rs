@@ -0,0 +1,229 @@
+use std::fmt::Display;
+use std::time::Duration;
+
+use chrono::{
+    DateTime, Datelike, Local, LocalResult, NaiveDate, NaiveTime, TimeZone, Timelike, Utc,
+};
+use chrono_tz::Tz;
+
+use crate::duration;
+
+const TIME_FORMATS: [&str; 4] = ["%H:%M", "%H:%M:%S", "%I:%M%p", "%I:%M %p"];
+
+/// A countdown worked out from a wall-clock time such as "14:30".
+pub struct Target {
+    pub remaining: Duration,
+    /// The target as shown next to the dial, e.g. "14:30" or "Tue 09:00 CET".
+    pub display: String,
+}
+
+/// Resolves `time` (`14:30`, `14:30:15`, `2:30pm`) on `date` (`2026-03-01`)
+/// in `time_zone` (an IANA name such as `Europe/Berlin`). Without a date the
+/// next occurrence of the time is used, so "09:00" late in the evening means
+/// tomorrow morning. Without a time zone the system's local time is used.
+pub fn resolve(time: &str, date: Option<&str>, time_zone: Option<&str>) -> Result<Target, String> {
+    let time = parse_time(time)?;
+    let date = date
+        .map(|date| {
+            NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+                .map_err(|_| format!("Invalid date \"{}\", expected YYYY-MM-DD", date.trim()))
+        })
+        .transpose()?;
+    let now = Utc::now();
+
+    match time_zone {
+        Some(name) => {
+            let tz: Tz = name
+                .trim()
+                .parse()
+                .map_err(|_| format!("Unknown time zone \"{}\"", name.trim()))?;
+            let target = target_in(&tz, now, time, date)?;
+            let display = format!("{} {}", display_time(&target, now), target.format("%Z"));
+            Ok(Target {
+                remaining: within_limit(until(&target, now), &display)?,
+                display,
+            })
+        }
+        None => {
+            let target = target_in(&Local, now, time, date)?;
+            let display = display_time(&target, now);
+            Ok(Target {
+                remaining: within_limit(until(&target, now), &display)?,
+                display,
+            })
+        }
+    }
+}
+
+// A target counts down like any other timer, so it can't be more than
+// `duration::MAX_SECONDS` away either.
+fn within_limit(remaining: Duration, display: &str) -> Result<Duration, String> {
+    let seconds = remaining.as_secs() + u64::from(remaining.subsec_nanos() > 0);
+    duration::check_seconds(seconds)
+        .map(|_| remaining)
+        .map_err(|e| format!("{} is too far away: {}", display, e))
+}
+
+fn parse_time(input: &str) -> Result<NaiveTime, String> {
+    let input = input.trim();
+    TIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveTime::parse_from_str(input, format).ok())
+        .ok_or_else(|| format!("Invalid time \"{}\", expected e.g. 14:30 or 2:30pm", input))
+}
+
+fn target_in<Z: TimeZone>(
+    tz: &Z,
+    now: DateTime<Utc>,
+    time: NaiveTime,
+    date: Option<NaiveDate>,
+) -> Result<DateTime<Z>, String> {
+    let today = now.with_timezone(tz).date_naive();
+    let target = local_datetime(tz, date.unwrap_or(today), time)?;
+    if target > now {
+        return Ok(target);
+    }
+    if date.is_some() {
+        return Err(format!("{} is in the past", target.naive_local()));
+    }
+    let tomorrow = today.succ_opt().ok_or("Date out of range")?;
+    local_datetime(tz, tomorrow, time)
+}
+
+// Around a clock change a local time can happen twice (take the first) or
+// not at all.
+fn local_datetime<Z: TimeZone>(
+    tz: &Z,
+    date: NaiveDate,
+    time: NaiveTime,
+) -> Result<DateTime<Z>, String> {
+    let naive = date.and_time(time);
+    match tz.from_local_datetime(&naive) {
+        LocalResult::Single(target) | LocalResult::Ambiguous(target, _) => Ok(target),
+        LocalResult::None => Err(format!(
+            "{} does not exist in that time zone (clock change)",
+            naive
+        )),
+    }
+}
+
+fn until<Z: TimeZone>(target: &DateTime<Z>, now: DateTime<Utc>) -> Duration {
+    target
+        .with_timezone(&Utc)
+        .signed_duration_since(now)
+        .to_std()
+        .unwrap_or_default()
+}
+
+// Just the time for today, the weekday within a week, the full date beyond.
+fn display_time<Z: TimeZone>(target: &DateTime<Z>, now: DateTime<Utc>) -> String
+where
+    Z::Offset: Display,
+{
+    let time = if target.second() == 0 {
+        "%H:%M"
+    } else {
+        "%H:%M:%S"
+    };
+    let today = now.with_timezone(&target.timezone()).date_naive();
+    let days_ahead = (target.date_naive() - today).num_days();
+    let day = match days_ahead {
+        0 => String::new(),
+        1..=6 => format!("{} ", target.weekday()),
+        _ => format!("{} ", target.format("%Y-%m-%d")),
+    };
+    format!("{}{}", day, target.format(time))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono_tz::Europe::Berlin;
+
+    // Sunday, 13:00 in Berlin.
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2026, 3, 1, 12, 0, 0).unwrap()
+    }
+
+    fn time(input: &str) -> NaiveTime {
+        parse_time(input).unwrap()
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(year, month, day)
+    }
+
+    #[test]
+    fn reads_24_and_12_hour_times() {
+        let half_past_two = NaiveTime::from_hms_opt(14, 30, 0).unwrap();
+        assert_eq!(parse_time("14:30"), Ok(half_past_two));
+        assert_eq!(parse_time(" 2:30pm "), Ok(half_past_two));
+        assert_eq!(parse_time("02:30 PM"), Ok(half_past_two));
+        assert_eq!(
+            parse_time("14:30:15"),
+            Ok(NaiveTime::from_hms_opt(14, 30, 15).unwrap())
+        );
+        for input in ["", "25:00", "14:60", "14", "noon"] {
+            assert!(parse_time(input).is_err(), "{:?} was accepted", input);
+        }
+    }
+
+    #[test]
+    fn a_time_already_past_today_means_tomorrow() {
+        let later = target_in(&Berlin, now(), time("14:30"), None).unwrap();
+        assert_eq!(later.date_naive(), date(2026, 3, 1).unwrap());
+        assert_eq!(until(&later, now()), Duration::from_secs(90 * 60));
+
+        let earlier = target_in(&Berlin, now(), time("12:00"), None).unwrap();
+        assert_eq!(earlier.date_naive(), date(2026, 3, 2).unwrap());
+        assert_eq!(until(&earlier, now()), Duration::from_secs(23 * 60 * 60));
+    }
+
+    #[test]
+    fn a_target_more_than_a_week_away_is_refused() {
+        let check = |date| {
+            let target = target_in(&Berlin, now(), time("13:00"), date).unwrap();
+            within_limit(until(&target, now()), "later")
+        };
+        assert_eq!(
+            check(date(2026, 3, 8)),
+            Ok(Duration::from_secs(duration::MAX_SECONDS))
+        );
+        assert!(check(date(2026, 3, 9)).is_err());
+        assert!(check(date(2027, 1, 1)).is_err());
+        // Part of a second past the week is still too far
+        let just_over = Duration::from_millis(duration::MAX_SECONDS * 1000 + 1);
+        assert!(within_limit(just_over, "later").is_err());
+    }
+
+    #[test]
+    fn a_past_date_is_refused() {
+        assert!(target_in(&Berlin, now(), time("12:00"), date(2026, 3, 1)).is_err());
+        assert!(target_in(&Berlin, now(), time("09:00"), date(2026, 2, 28)).is_err());
+        assert!(target_in(&Berlin, now(), time("09:00"), date(2026, 3, 2)).is_ok());
+    }
+
+    #[test]
+    fn clock_changes_skip_or_repeat_an_hour() {
+        let skipped = local_datetime(&Berlin, date(2026, 3, 29).unwrap(), time("02:30"));
+        assert!(skipped.is_err());
+
+        let repeated = local_datetime(&Berlin, date(2026, 10, 25).unwrap(), time("02:30")).unwrap();
+        assert_eq!(
+            repeated.with_timezone(&Utc),
+            Utc.with_ymd_and_hms(2026, 10, 25, 0, 30, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn shows_the_day_only_when_it_is_not_today() {
+        let show = |time: &str, date: Option<NaiveDate>| {
+            let target = target_in(&Berlin, now(), parse_time(time).unwrap(), date).unwrap();
+            display_time(&target, now())
+        };
+        assert_eq!(show("14:30", None), "14:30");
+        assert_eq!(show("12:00", None), "Mon 12:00");
+        assert_eq!(show("09:00:30", date(2026, 3, 7)), "Sat 09:00:30");
+        assert_eq!(show("09:00", date(2026, 3, 10)), "2026-03-10 09:00");
+    }
+}
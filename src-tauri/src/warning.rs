@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+
+/// A heads-up some time before a timer (or Pomodoro phase, or sequence
+/// step) runs out, part of the settings file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Warning {
+    pub seconds_left: u64,
+    pub sound: bool,
+    pub notification: bool,
+    /// A stronger flash of the whole dial than the per-minute tick flash.
+    pub flash: bool,
+}
+
+impl Default for Warning {
+    fn default() -> Self {
+        Self {
+            seconds_left: 60,
+            sound: false,
+            notification: false,
+            flash: true,
+        }
+    }
+}
+
+/// Five minutes and one minute before the end, both just flashing.
+pub fn default_warnings() -> Vec<Warning> {
+    vec![
+        Warning {
+            seconds_left: 5 * 60,
+            ..Warning::default()
+        },
+        Warning::default(),
+    ]
+}
+
+pub fn validate(warnings: &[Warning]) -> Result<(), String> {
+    for (index, warning) in warnings.iter().enumerate() {
+        if warning.seconds_left == 0 {
+            return Err("Warnings must come before the end".to_string());
+        }
+        if warnings[..index]
+            .iter()
+            .any(|other| other.seconds_left == warning.seconds_left)
+        {
+            return Err(format!(
+                "There is more than one warning at {} s left",
+                warning.seconds_left
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Finds the warning that was set up for `seconds_left`.
+pub fn find(warnings: &[Warning], seconds_left: u64) -> Option<&Warning> {
+    warnings
+        .iter()
+        .find(|warning| warning.seconds_left == seconds_left)
+}
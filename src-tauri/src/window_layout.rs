@@ -0,0 +1,223 @@
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use tauri::{
+    AppHandle, LogicalPosition, LogicalSize, Monitor, PhysicalPosition, PhysicalSize, WebviewWindow,
+};
+
+use crate::persistence;
+
+const LAYOUT_FILE: &str = "window.json";
+
+// How much of the window has to land on a monitor for a saved position to
+// be used, in pixels each way; enough to grab it and drag it back.
+const MIN_VISIBLE: i32 = 80;
+
+/// What the webview draws: the size preset and whether minute labels show.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowView {
+    pub preset: String,
+    pub labels_visible: bool,
+}
+
+impl Default for WindowView {
+    fn default() -> Self {
+        Self {
+            preset: "preset-m".to_string(),
+            labels_visible: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct WindowLayout {
+    /// Logical pixels, scaled by the monitor the window was on, so the
+    /// window comes back at the same size on a display of another scale.
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+    #[serde(flatten)]
+    view: WindowView,
+}
+
+/// Stored as `window.json`: one layout per set of connected monitors, so
+/// docking and undocking a laptop each bring back their own.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct SavedLayouts {
+    by_monitors: BTreeMap<String, WindowLayout>,
+    /// The monitor set saved most recently.
+    last: Option<String>,
+}
+
+/// Managed as Tauri state.
+pub struct WindowLayouts {
+    saved: Mutex<SavedLayouts>,
+    view: Mutex<WindowView>,
+}
+
+impl WindowLayouts {
+    pub fn load(app: &AppHandle) -> Self {
+        Self {
+            saved: Mutex::new(persistence::read_json(app, LAYOUT_FILE).unwrap_or_default()),
+            view: Mutex::new(WindowView::default()),
+        }
+    }
+
+    /// The view restored at startup, or last reported by the webview.
+    pub fn view(&self) -> WindowView {
+        self.view.lock().unwrap().clone()
+    }
+
+    /// Puts the window back where it was with the monitors now connected.
+    /// With a monitor set never seen before, the last size and view are
+    /// kept but the window is centered, as its old spot may be gone.
+    pub fn restore(&self, window: &WebviewWindow) {
+        let monitors = window.available_monitors().unwrap_or_default();
+        let saved = self.saved.lock().unwrap();
+        let (layout, same_monitors) = match saved.by_monitors.get(&monitor_key(&monitors)) {
+            Some(layout) => (layout, true),
+            None => match saved
+                .last
+                .as_ref()
+                .and_then(|key| saved.by_monitors.get(key))
+            {
+                Some(layout) => (layout, false),
+                None => return,
+            },
+        };
+
+        let size = LogicalSize::new(layout.width, layout.height);
+        let position = LogicalPosition::new(layout.x, layout.y);
+        // Back in pixels of the monitor the window is going to be on
+        let placed = same_monitors
+            .then(|| monitor_under(&monitors, position, size))
+            .flatten()
+            .map(|monitor| {
+                let scale = monitor.scale_factor();
+                (
+                    position.to_physical::<i32>(scale),
+                    size.to_physical::<u32>(scale),
+                )
+            })
+            .filter(|&(position, size)| is_reachable(&monitors, position, size));
+        // Otherwise the logical size is scaled for the monitor it opened on
+        let result = match placed {
+            Some((position, size)) => window
+                .set_size(size)
+                .and_then(|_| window.set_position(position)),
+            None => window.set_size(size).and_then(|_| window.center()),
+        };
+        if let Err(e) = result {
+            eprintln!("Failed to restore window layout: {}", e);
+        }
+        *self.view.lock().unwrap() = layout.view.clone();
+    }
+
+    /// Records a new view from the webview along with where the window is.
+    pub fn set_view(&self, app: &AppHandle, window: &WebviewWindow, view: WindowView) {
+        *self.view.lock().unwrap() = view;
+        self.save(app, window);
+    }
+
+    /// Writes the window's position and size for the monitors connected now.
+    /// A minimized window has no useful position, so the last one is kept.
+    pub fn save(&self, app: &AppHandle, window: &WebviewWindow) {
+        if window.is_minimized().unwrap_or(false) {
+            return;
+        }
+        let geometry = window.scale_factor().and_then(|scale| {
+            Ok((
+                window.outer_position()?.to_logical::<f64>(scale),
+                window.inner_size()?.to_logical::<f64>(scale),
+            ))
+        });
+        let (position, size) = match geometry {
+            Ok(geometry) => geometry,
+            Err(e) => {
+                eprintln!("Failed to read window layout: {}", e);
+                return;
+            }
+        };
+        let key = monitor_key(&window.available_monitors().unwrap_or_default());
+
+        let mut saved = self.saved.lock().unwrap();
+        saved.by_monitors.insert(
+            key.clone(),
+            WindowLayout {
+                x: position.x,
+                y: position.y,
+                width: size.width,
+                height: size.height,
+                view: self.view(),
+            },
+        );
+        saved.last = Some(key);
+        if let Err(e) = persistence::write_json(app, LAYOUT_FILE, &*saved) {
+            eprintln!("Failed to save window layout: {}", e);
+        }
+    }
+}
+
+// Names, positions and sizes of all monitors, in a stable order.
+fn monitor_key(monitors: &[Monitor]) -> String {
+    let mut parts: Vec<String> = monitors
+        .iter()
+        .map(|monitor| {
+            let (position, size) = (monitor.position(), monitor.size());
+            format!(
+                "{}@{},{}:{}x{}",
+                monitor.name().map(String::as_str).unwrap_or("?"),
+                position.x,
+                position.y,
+                size.width,
+                size.height
+            )
+        })
+        .collect();
+    parts.sort();
+    parts.join("|")
+}
+
+// The monitor under the middle of a window at `position`, in the logical
+// pixels of each monitor.
+fn monitor_under(
+    monitors: &[Monitor],
+    position: LogicalPosition<f64>,
+    size: LogicalSize<f64>,
+) -> Option<&Monitor> {
+    let (x, y) = (
+        position.x + size.width / 2.0,
+        position.y + size.height / 2.0,
+    );
+    monitors.iter().find(|monitor| {
+        let scale = monitor.scale_factor();
+        let origin = monitor.position().to_logical::<f64>(scale);
+        let area = monitor.size().to_logical::<f64>(scale);
+        (origin.x..origin.x + area.width).contains(&x)
+            && (origin.y..origin.y + area.height).contains(&y)
+    })
+}
+
+fn is_reachable(
+    monitors: &[Monitor],
+    position: PhysicalPosition<i32>,
+    size: PhysicalSize<u32>,
+) -> bool {
+    let (right, bottom) = (
+        position.x + size.width as i32,
+        position.y + size.height as i32,
+    );
+    monitors.iter().any(|monitor| {
+        let (area_position, area_size) = (monitor.position(), monitor.size());
+        let overlap_x =
+            right.min(area_position.x + area_size.width as i32) - position.x.max(area_position.x);
+        let overlap_y =
+            bottom.min(area_position.y + area_size.height as i32) - position.y.max(area_position.y);
+        overlap_x >= MIN_VISIBLE && overlap_y >= MIN_VISIBLE
+    })
+}